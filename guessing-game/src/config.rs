@@ -0,0 +1,272 @@
+use crate::{
+    messages::{Catalogue, Locale, MessageId},
+    variants::VariantKind,
+    Difficulty,
+};
+use std::{fmt::Display, iter::Peekable, path::PathBuf};
+
+pub const DEFAULT_SCORES_FILE: &str = "scores.txt";
+pub const DEFAULT_TOP: usize = 10;
+pub const DEFAULT_AUTO_RUNS: u32 = 1000;
+pub const DEFAULT_HINTS: u32 = 3;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Mode {
+    Play,
+    // Print the best N scores for each difficulty and exit
+    Scores { top: usize },
+    // Let the binary search solver play against itself
+    Auto { runs: u32 },
+    // Host a multiplayer game over TCP
+    Serve { addr: String },
+    // Re-run a recorded session and check its hints
+    Replay { path: PathBuf },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Config {
+    pub mode: Mode,
+    pub difficulty: Difficulty,
+    pub variant: VariantKind,
+    pub player: Option<String>,
+    pub scores_file: PathBuf,
+    pub seed: Option<u64>,
+    pub record: Option<PathBuf>,
+    pub locale: Locale,
+    // How many clues the player may spend, None when hints are off
+    pub hints: Option<u32>,
+}
+
+impl Config {
+    // Same idea as minigrep's Config::build_v2 - we take the raw
+    // iterator from env::args() and skip the program name ourselves.
+    // `locale` is what to use when there's no --lang, main reads it
+    // from LANG so the config itself never touches the environment.
+    // Errors come back in whichever language is set when they happen,
+    // so "--lang fr --bogus" already complains in French.
+    pub fn build(args: impl Iterator<Item = String>, locale: Locale) -> Result<Config, String> {
+        let mut args = args.skip(1).peekable();
+        let mut config = Config {
+            mode: Mode::Play,
+            difficulty: Difficulty::default(),
+            variant: VariantKind::default(),
+            player: None,
+            scores_file: PathBuf::from(DEFAULT_SCORES_FILE),
+            seed: None,
+            record: None,
+            locale,
+            hints: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scores" => {
+                    // The count is optional: "--scores" alone shows the top 10
+                    let top = optional_number(&mut args).unwrap_or(DEFAULT_TOP);
+                    config.mode = Mode::Scores { top };
+                }
+                "--auto" => {
+                    let runs = optional_number(&mut args).unwrap_or(DEFAULT_AUTO_RUNS);
+                    config.mode = Mode::Auto { runs };
+                }
+                "--serve" => {
+                    let addr = value_for(config.locale, &arg, args.next())?;
+                    config.mode = Mode::Serve { addr };
+                }
+                "--record" => {
+                    config.record =
+                        Some(PathBuf::from(value_for(config.locale, &arg, args.next())?));
+                }
+                "--replay" => {
+                    let path = PathBuf::from(value_for(config.locale, &arg, args.next())?);
+                    config.mode = Mode::Replay { path };
+                }
+                "--hints" => {
+                    config.hints = Some(optional_number(&mut args).unwrap_or(DEFAULT_HINTS));
+                }
+                "--seed" => {
+                    let seed = value_for(config.locale, &arg, args.next())?;
+                    config.seed = Some(seed.parse().map_err(|_| {
+                        refuse(config.locale, MessageId::BadSeed, &[("seed", &seed)])
+                    })?);
+                }
+                "--difficulty" => {
+                    let difficulty = value_for(config.locale, &arg, args.next())?;
+                    config.difficulty = difficulty.parse().map_err(|_| {
+                        let args = [("difficulty", &difficulty as &dyn Display)];
+                        refuse(config.locale, MessageId::UnknownDifficulty, &args)
+                    })?;
+                }
+                "--variant" => {
+                    let variant = value_for(config.locale, &arg, args.next())?;
+                    config.variant = variant.parse().map_err(|_| {
+                        refuse(
+                            config.locale,
+                            MessageId::UnknownVariant,
+                            &[("variant", &variant)],
+                        )
+                    })?;
+                }
+                "--lang" => {
+                    let lang = value_for(config.locale, &arg, args.next())?;
+                    config.locale = lang.parse().map_err(|_| {
+                        refuse(
+                            config.locale,
+                            MessageId::UnsupportedLanguage,
+                            &[("lang", &lang)],
+                        )
+                    })?;
+                }
+                "--name" => config.player = Some(value_for(config.locale, &arg, args.next())?),
+                "--scores-file" => {
+                    config.scores_file =
+                        PathBuf::from(value_for(config.locale, &arg, args.next())?);
+                }
+                other => {
+                    let args = [("argument", &other as &dyn Display)];
+                    return Err(refuse(config.locale, MessageId::UnexpectedArgument, &args));
+                }
+            }
+        }
+
+        if config.hints.is_some() && config.variant != VariantKind::Number {
+            return Err(refuse(config.locale, MessageId::HintsNeedNumbers, &[]));
+        }
+
+        Ok(config)
+    }
+}
+
+// Consumes the next argument only if it is a number
+fn optional_number<T: std::str::FromStr>(
+    args: &mut Peekable<impl Iterator<Item = String>>,
+) -> Option<T> {
+    let number = args.peek()?.parse().ok()?;
+    args.next();
+    Some(number)
+}
+
+fn value_for(locale: Locale, flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| refuse(locale, MessageId::NeedsValue, &[("flag", &flag)]))
+}
+
+// Only built once something has gone wrong, so a good command line
+// never pays for a catalogue
+fn refuse(locale: Locale, id: MessageId, args: &[(&str, &dyn Display)]) -> String {
+    Catalogue::new(locale).format(id, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(args: &[&str]) -> Result<Config, String> {
+        let args = ["guessing-game"].iter().chain(args).map(|a| a.to_string());
+        Config::build(args, Locale::default())
+    }
+
+    #[test]
+    fn defaults_to_playing() {
+        let config = build(&[]).unwrap();
+        assert_eq!(Mode::Play, config.mode);
+        assert_eq!(Difficulty::Hard, config.difficulty);
+        assert_eq!(VariantKind::Number, config.variant);
+        assert_eq!(None, config.player);
+    }
+
+    #[test]
+    fn scores_count_is_optional() {
+        assert_eq!(
+            Mode::Scores { top: DEFAULT_TOP },
+            build(&["--scores"]).unwrap().mode
+        );
+        let config = build(&["--scores", "3", "--difficulty", "easy"]).unwrap();
+        assert_eq!(Mode::Scores { top: 3 }, config.mode);
+        assert_eq!(Difficulty::Easy, config.difficulty);
+    }
+
+    #[test]
+    fn auto_play_with_seed() {
+        let config = build(&["--auto", "--seed", "7"]).unwrap();
+        assert_eq!(
+            Mode::Auto {
+                runs: DEFAULT_AUTO_RUNS
+            },
+            config.mode
+        );
+        assert_eq!(Some(7), config.seed);
+        assert_eq!(
+            Mode::Auto { runs: 20 },
+            build(&["--auto", "20"]).unwrap().mode
+        );
+    }
+
+    #[test]
+    fn serve_takes_an_address() {
+        assert_eq!(
+            Mode::Serve {
+                addr: String::from("0.0.0.0:7878")
+            },
+            build(&["--serve", "0.0.0.0:7878"]).unwrap().mode
+        );
+    }
+
+    #[test]
+    fn record_and_replay_take_paths() {
+        let config = build(&["--record", "game.log", "--seed", "3"]).unwrap();
+        assert_eq!(Some(PathBuf::from("game.log")), config.record);
+        assert_eq!(
+            Mode::Replay {
+                path: PathBuf::from("game.log")
+            },
+            build(&["--replay", "game.log"]).unwrap().mode
+        );
+    }
+
+    #[test]
+    fn lang_flag_picks_the_locale() {
+        assert_eq!(Locale::French, build(&["--lang", "fr"]).unwrap().locale);
+        let args = ["guessing-game", "--lang", "en"].map(String::from);
+        let config = Config::build(args.into_iter(), Locale::French).unwrap();
+        assert_eq!(Locale::English, config.locale);
+        let args = ["guessing-game"].map(String::from);
+        let config = Config::build(args.into_iter(), Locale::French).unwrap();
+        assert_eq!(Locale::French, config.locale);
+    }
+
+    #[test]
+    fn hints_budget_is_optional() {
+        assert_eq!(None, build(&[]).unwrap().hints);
+        assert_eq!(Some(DEFAULT_HINTS), build(&["--hints"]).unwrap().hints);
+        assert_eq!(Some(1), build(&["--hints", "1"]).unwrap().hints);
+        assert!(build(&["--hints", "--variant", "word"]).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(build(&["--name"]).is_err());
+        assert!(build(&["--serve"]).is_err());
+        assert!(build(&["--replay"]).is_err());
+        assert!(build(&["--lang", "xx"]).is_err());
+        assert!(build(&["--difficulty", "nightmare"]).is_err());
+        assert!(build(&["--variant", "chess"]).is_err());
+        assert!(build(&["--bogus"]).is_err());
+        assert!(build(&["--seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn errors_follow_the_chosen_language() {
+        assert_eq!(
+            Err(String::from("unexpected argument '--bogus'")),
+            build(&["--bogus"])
+        );
+        assert_eq!(
+            Err(String::from("argument inattendu '--bogus'")),
+            build(&["--lang", "fr", "--bogus"])
+        );
+        assert_eq!(
+            Err(String::from("--name attend une valeur")),
+            build(&["--lang", "fr", "--name"])
+        );
+    }
+}
@@ -0,0 +1,242 @@
+// Optional hints on top of the plain Too small! / Too big! answer.
+// After every guess the player hears whether they got warmer or colder
+// than their previous guess, and roughly how far away they are. They
+// can also spend a limited number of clues about the secret itself.
+//
+// This module sits under the crate root, so it can peek at the Game's
+// private secret without the secret being public to everyone else.
+
+use crate::{Game, Guess};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Temperature {
+    Warmer,
+    Colder,
+    // Exactly as far away as last time, whether that's the same guess
+    // again or the same gap on the other side of the secret
+    Same,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Distance {
+    // Within 3
+    Boiling,
+    // Within 10
+    Hot,
+    // Within 25
+    Warm,
+    Cold,
+}
+
+impl Distance {
+    fn from_gap(gap: i32) -> Distance {
+        match gap {
+            0..=3 => Distance::Boiling,
+            4..=10 => Distance::Hot,
+            11..=25 => Distance::Warm,
+            _ => Distance::Cold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proximity {
+    // None on the very first guess, there's nothing to compare against
+    pub temperature: Option<Temperature>,
+    pub distance: Distance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClueKind {
+    Parity,
+    Quarter,
+}
+
+impl FromStr for ClueKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ClueKind, String> {
+        match s.trim().to_lowercase().as_str() {
+            "parity" | "even" | "odd" => Ok(ClueKind::Parity),
+            "quarter" => Ok(ClueKind::Quarter),
+            other => Err(format!("unknown hint '{}'", other)),
+        }
+    }
+}
+
+// The player asks for a clue by typing "hint" and then which kind, e.g.
+// "hint parity". Hands back the kind part, or None when the line isn't
+// asking for a hint at all - "hints" or "hintparity" are just bad guesses.
+pub fn requested(line: &str) -> Option<&str> {
+    let rest = line.trim().strip_prefix("hint")?;
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clue {
+    Even,
+    Odd,
+    // Which quarter of the difficulty's range (1 to 4) and its bounds
+    Quarter { quarter: u8, low: i32, high: i32 },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NoHintsLeft;
+
+impl fmt::Display for NoHintsLeft {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no hints left")
+    }
+}
+
+pub struct Hints {
+    previous_gap: Option<i32>,
+    remaining: u32,
+}
+
+impl Hints {
+    pub fn new(budget: u32) -> Hints {
+        Hints {
+            previous_gap: None,
+            remaining: budget,
+        }
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    // Call once per guess, after the game has answered it
+    pub fn after_guess(&mut self, game: &Game, guess: &Guess) -> Proximity {
+        let gap = (guess.value() - game.secret_number.value()).abs();
+        let temperature = self.previous_gap.map(|previous| {
+            if gap < previous {
+                Temperature::Warmer
+            } else if gap > previous {
+                Temperature::Colder
+            } else {
+                Temperature::Same
+            }
+        });
+        self.previous_gap = Some(gap);
+        Proximity {
+            temperature,
+            distance: Distance::from_gap(gap),
+        }
+    }
+
+    pub fn spend(&mut self, game: &Game, kind: ClueKind) -> Result<Clue, NoHintsLeft> {
+        if self.remaining == 0 {
+            return Err(NoHintsLeft);
+        }
+        self.remaining -= 1;
+
+        let secret = game.secret_number.value();
+        Ok(match kind {
+            ClueKind::Parity if secret % 2 == 0 => Clue::Even,
+            ClueKind::Parity => Clue::Odd,
+            ClueKind::Quarter => {
+                let range = game.difficulty().range();
+                let (start, size) = (*range.start(), range.end() - range.start() + 1);
+                let bounds = |quarter: i32| {
+                    (
+                        start + size * (quarter - 1) / 4,
+                        start + size * quarter / 4 - 1,
+                    )
+                };
+                let quarter = (1..=4).find(|&q| secret <= bounds(q).1).unwrap_or(4);
+                let (low, high) = bounds(quarter);
+                Clue::Quarter {
+                    quarter: quarter as u8,
+                    low,
+                    high,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Difficulty, SecretSource};
+    use std::ops::RangeInclusive;
+
+    struct Fixed(i32);
+
+    impl SecretSource for Fixed {
+        fn pick(&mut self, _range: RangeInclusive<i32>) -> i32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn warmer_and_colder_compare_with_the_previous_guess() {
+        let game = Game::new(&mut Fixed(40));
+        let mut hints = Hints::new(0);
+        let mut check = |guess| hints.after_guess(&game, &Guess::new(guess));
+
+        assert_eq!(
+            Proximity {
+                temperature: None,
+                distance: Distance::Cold
+            },
+            check(90)
+        );
+        assert_eq!(Some(Temperature::Warmer), check(60).temperature);
+        assert_eq!(Some(Temperature::Same), check(20).temperature);
+        assert_eq!(Some(Temperature::Colder), check(1).temperature);
+        assert_eq!(Distance::Hot, check(50).distance);
+        assert_eq!(Distance::Boiling, check(38).distance);
+    }
+
+    #[test]
+    fn hint_requests_are_a_word_of_their_own() {
+        assert_eq!(Some("parity"), requested("hint parity"));
+        assert_eq!(Some("quarter"), requested("  hint   quarter \n"));
+        assert_eq!(Some(""), requested("hint"));
+        assert_eq!(None, requested("hintparity"));
+        assert_eq!(None, requested("hints"));
+        assert_eq!(None, requested("42"));
+    }
+
+    #[test]
+    fn clues_are_limited() {
+        let game = Game::new(&mut Fixed(42));
+        let mut hints = Hints::new(2);
+        assert_eq!(Ok(Clue::Even), hints.spend(&game, ClueKind::Parity));
+        assert_eq!(1, hints.remaining());
+        assert_eq!(
+            Ok(Clue::Quarter {
+                quarter: 2,
+                low: 26,
+                high: 50
+            }),
+            hints.spend(&game, ClueKind::Quarter)
+        );
+        assert_eq!(Err(NoHintsLeft), hints.spend(&game, ClueKind::Parity));
+    }
+
+    #[test]
+    fn quarters_follow_the_difficulty_range() {
+        let quarter = |secret| {
+            let game = Game::with_difficulty(&mut Fixed(secret), Difficulty::Easy);
+            match Hints::new(1).spend(&game, ClueKind::Quarter).unwrap() {
+                Clue::Quarter { quarter, low, high } => {
+                    assert!((low..=high).contains(&secret));
+                    quarter
+                }
+                other => panic!("expected a quarter, got {:?}", other),
+            }
+        };
+        let quarters: Vec<u8> = (1..=10).map(quarter).collect();
+        assert_eq!(vec![1, 1, 2, 2, 2, 3, 3, 4, 4, 4], quarters);
+        assert_eq!(Ok(ClueKind::Quarter), "Quarter".parse());
+        assert!("colour".parse::<ClueKind>().is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use messages::{Catalogue, Locale, MessageId};
+use rand::Rng;
+use std::{cmp::Ordering, fmt, ops::RangeInclusive, str::FromStr};
+
+pub mod config;
+pub mod hints;
+pub mod messages;
+pub mod scores;
+pub mod server;
+pub mod session;
+pub mod solver;
+pub mod variants;
+
+pub const MIN_GUESS: i32 = 1;
+pub const MAX_GUESS: i32 = 100;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Guess {
+    value: i32,
+}
+
+impl Guess {
+    pub fn new(value: i32) -> Guess {
+        if value < MIN_GUESS {
+            out_of_range(MessageId::GuessTooLow, value);
+        } else if value > MAX_GUESS {
+            out_of_range(MessageId::GuessTooHigh, value);
+        }
+        Guess { value }
+    }
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+}
+
+// A bad value in Guess::new is a bug in the caller rather than something
+// the player typed, so the message is always the English one. Only built
+// once we know we're panicking - every guess goes through Guess::new.
+#[cold]
+fn out_of_range(id: MessageId, value: i32) -> ! {
+    let messages = Catalogue::new(Locale::English);
+    panic!(
+        "{}",
+        messages.format(
+            id,
+            &[("min", &MIN_GUESS), ("max", &MAX_GUESS), ("value", &value)]
+        )
+    );
+}
+
+// impl std::cmp::Ord for Guess {
+//     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+//         if self.value < other.value {
+//             Ordering::Less
+//         } else if self.value > other.value {
+//             Ordering::Greater
+//         } else {
+//             Ordering::Equal
+//         }
+//     }
+// }
+
+// impl std::cmp::PartialOrd for Guess {
+//     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+//         if self.value < other.value {
+//             Some(Ordering::Less)
+//         } else if self.value > other.value {
+//             Some(Ordering::Greater)
+//         } else {
+//             Some(Ordering::Equal)
+//         }
+//     }
+// }
+
+// impl std::cmp::Eq for Guess {
+//     fn assert_receiver_is_total_eq(&self) {}
+// }
+
+// impl std::cmp::PartialEq for Guess {
+//     fn eq(&self, other: &Self) -> bool {
+//         self.value == other.value
+//     }
+// }
+
+// Guess::new() panics on bad input, which is fine for values we
+// control. Player input is a different story, so parsing hands back
+// a Result and lets the caller decide what to do with a bad line.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseGuessError {
+    NotANumber,
+    OutOfRange(i32),
+}
+
+impl fmt::Display for ParseGuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseGuessError::NotANumber => write!(f, "that is not a number"),
+            ParseGuessError::OutOfRange(value) => write!(
+                f,
+                "{} is not between {} and {}",
+                value, MIN_GUESS, MAX_GUESS
+            ),
+        }
+    }
+}
+
+impl FromStr for Guess {
+    type Err = ParseGuessError;
+
+    fn from_str(s: &str) -> Result<Guess, ParseGuessError> {
+        let value: i32 = s.trim().parse().map_err(|_| ParseGuessError::NotANumber)?;
+        if !(MIN_GUESS..=MAX_GUESS).contains(&value) {
+            return Err(ParseGuessError::OutOfRange(value));
+        }
+        Ok(Guess { value })
+    }
+}
+
+// Anything that can pick a secret number. Every rand::Rng gets this
+// for free, so the CLI can pass thread_rng() while tests pass a
+// seeded StdRng (or their own fixed source).
+pub trait SecretSource {
+    fn pick(&mut self, range: RangeInclusive<i32>) -> i32;
+}
+
+impl<R: Rng> SecretSource for R {
+    fn pick(&mut self, range: RangeInclusive<i32>) -> i32 {
+        self.gen_range(range)
+    }
+}
+
+// Each difficulty narrows the range the secret is picked from. Guesses
+// are still validated against the full 1-100 range, so on Easy a guess
+// of 60 is simply "Too big!".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    // Hard is the classic 1-100 game
+    #[default]
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn range(&self) -> RangeInclusive<i32> {
+        match self {
+            Difficulty::Easy => MIN_GUESS..=10,
+            Difficulty::Medium => MIN_GUESS..=50,
+            Difficulty::Hard => MIN_GUESS..=MAX_GUESS,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Difficulty, String> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            other => Err(format!("unknown difficulty '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    TooSmall,
+    TooBig,
+    Win,
+}
+
+impl Outcome {
+    // The Ordering of guess.cmp(&secret_number) that produced this outcome
+    pub fn ordering(&self) -> Ordering {
+        match self {
+            Outcome::TooSmall => Ordering::Less,
+            Outcome::TooBig => Ordering::Greater,
+            Outcome::Win => Ordering::Equal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Playing,
+    Won,
+}
+
+pub struct Game {
+    secret_number: Guess,
+    difficulty: Difficulty,
+    attempts: u32,
+    state: State,
+}
+
+impl Game {
+    pub fn new(source: &mut impl SecretSource) -> Game {
+        Game::with_difficulty(source, Difficulty::default())
+    }
+
+    pub fn with_difficulty(source: &mut impl SecretSource, difficulty: Difficulty) -> Game {
+        Game {
+            secret_number: Guess::new(source.pick(difficulty.range())),
+            difficulty,
+            attempts: 0,
+            state: State::Playing,
+        }
+    }
+
+    // Once the game is won further guesses are still answered, but
+    // they no longer count towards the number of attempts.
+    pub fn guess(&mut self, guess: &Guess) -> Outcome {
+        // 17th Jan:
+        // Ooft, "guess" of type Guess is a custom type
+        // and it dosen't look like we know how to compare two
+        // instances of a Guess...
+        // We will fix this later...with TRAITS!
+
+        // 19th Jan:
+        // Have a look at the updated implementation
+        // above where we have defined how to compare instances
+        // of Guess. We have implemented the relevant traits on the type.
+
+        // 22nd Jan:
+        // Another update! This time, we are deriving the
+        // traits for PartialEq, Eq, PartialOrd, Ord from the standard
+        // library which does the trick!
+
+        // Hence, we should derive behaviour from the base traits (as
+        // it often suffices). However, if we wish to change the default
+        // behaviour / implementation of these traits, we can override them
+        // explicitly like we did before.
+        let outcome = match guess.cmp(&self.secret_number) {
+            Ordering::Less => Outcome::TooSmall,
+            Ordering::Greater => Outcome::TooBig,
+            Ordering::Equal => Outcome::Win,
+        };
+        if self.state == State::Playing {
+            self.attempts += 1;
+            if outcome == Outcome::Win {
+                self.state = State::Won;
+            }
+        }
+        outcome
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+}
+
+// Unit tests in Rust go in the same file as the code
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // A secret source that always picks the same number
+    struct Fixed(i32);
+
+    impl SecretSource for Fixed {
+        fn pick(&mut self, _range: RangeInclusive<i32>) -> i32 {
+            self.0
+        }
+    }
+
+    #[test]
+    // The #[should_panic] annotation asserts if the test
+    // causes a panic
+    #[should_panic]
+    fn greater_than_100() {
+        Guess::new(200);
+    }
+
+    #[test]
+    // The "expected" parameter takes in a substring of the error
+    // message that should be present in the panic!()
+    // The below test panics with an error message containing the
+    // expected substring - thus it passes! If the error message
+    // is incorrect, this test will fail.
+
+    // THIS IS A REALLY GOOD WAY OF MAKING SURE THAT A TEST
+    // PANICS FOR THE "RIGHT / EXPECTED" REASON!
+    #[should_panic(expected = "less than or equal to 100")]
+    fn greater_than_100_with_expected() {
+        Guess::new(200);
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        assert_eq!(Ok(Guess::new(42)), " 42\n".parse::<Guess>());
+        assert_eq!(Err(ParseGuessError::NotANumber), "abc".parse::<Guess>());
+        assert_eq!(Err(ParseGuessError::OutOfRange(0)), "0".parse::<Guess>());
+        assert_eq!(
+            Err(ParseGuessError::OutOfRange(101)),
+            "101".parse::<Guess>()
+        );
+    }
+
+    #[test]
+    fn game_reports_outcomes_until_won() {
+        let mut game = Game::new(&mut Fixed(37));
+        assert_eq!(Outcome::TooSmall, game.guess(&Guess::new(10)));
+        assert_eq!(Outcome::TooBig, game.guess(&Guess::new(80)));
+        assert_eq!(State::Playing, game.state());
+        assert_eq!(Outcome::Win, game.guess(&Guess::new(37)));
+        assert_eq!(State::Won, game.state());
+        assert_eq!(3, game.attempts());
+
+        // Guessing after the win doesn't bump the attempt count
+        game.guess(&Guess::new(50));
+        assert_eq!(3, game.attempts());
+    }
+
+    #[test]
+    fn seeded_rng_picks_the_same_secret() {
+        let secret = |seed| {
+            let mut game = Game::new(&mut StdRng::seed_from_u64(seed));
+            (MIN_GUESS..=MAX_GUESS)
+                .find(|&n| game.guess(&Guess::new(n)) == Outcome::Win)
+                .unwrap()
+        };
+        assert_eq!(secret(7), secret(7));
+    }
+
+    #[test]
+    fn difficulty_limits_the_secret() {
+        for seed in 0..50 {
+            let mut game =
+                Game::with_difficulty(&mut StdRng::seed_from_u64(seed), Difficulty::Easy);
+            assert_eq!(Outcome::TooBig, game.guess(&Guess::new(11)));
+        }
+        assert_eq!(Ok(Difficulty::Medium), "Medium".parse());
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+}
@@ -1,142 +1,351 @@
-use rand::Rng;
-use std::{cmp::Ordering, io};
+use fsg_guessing_game::{
+    config::{Config, Mode},
+    hints::{self, Clue, ClueKind, Distance, Hints, NoHintsLeft, Proximity, Temperature},
+    messages::{Catalogue, Locale, MessageId},
+    scores::{Leaderboard, Score},
+    server::Server,
+    session::{Recorder, Session},
+    solver,
+    variants::{Feedback, InvalidGuess, Mark, VariantKind},
+    Difficulty, Game, Guess, Outcome, ParseGuessError, MAX_GUESS, MIN_GUESS,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{env, error::Error, io, path::Path, process, time::Instant};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Guess {
-    value: i32,
+fn main() {
+    // LANG picks the language until --lang says otherwise, so even a
+    // bad command line gets its error in the right language
+    let locale = env::var("LANG")
+        .ok()
+        .and_then(|lang| Locale::from_tag(&lang))
+        .unwrap_or_default();
+    let config = Config::build(env::args(), locale).unwrap_or_else(|err| {
+        let messages = Catalogue::new(locale);
+        eprintln!(
+            "{}",
+            messages.format(MessageId::ArgumentsError, &[("error", &err)])
+        );
+        process::exit(1);
+    });
+    let messages = Catalogue::new(config.locale);
+
+    let result = match config.mode {
+        Mode::Play => play(&config, &messages),
+        Mode::Scores { top } => show_scores(&config, &messages, top),
+        Mode::Auto { runs } => {
+            auto_play(&config, &messages, runs);
+            Ok(())
+        }
+        Mode::Serve { ref addr } => serve(&config, &messages, addr),
+        Mode::Replay { ref path } => replay(&messages, path),
+    };
+
+    if let Err(err) = result {
+        eprintln!(
+            "{}",
+            messages.format(MessageId::ApplicationError, &[("error", &err)])
+        );
+        process::exit(1);
+    }
 }
 
-impl Guess {
-    pub fn new(value: i32) -> Guess {
-        if value < 1 {
-            panic!(
-                "Guess value must be greater than or equal to 1, got {} instead.",
-                value
-            );
-        } else if value > 100 {
-            panic!(
-                "Guess value must be less than or equal to 100, got {} instead.",
-                value
+fn play(config: &Config, messages: &Catalogue) -> Result<(), Box<dyn Error>> {
+    println!(
+        "{}",
+        messages.get(match config.variant {
+            VariantKind::Number => MessageId::GuessTheNumber,
+            VariantKind::BullsAndCows => MessageId::GuessTheDigits,
+            VariantKind::Word => MessageId::GuessTheWord,
+        })
+    );
+
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut game = config
+        .variant
+        .start(&mut StdRng::seed_from_u64(seed), config.difficulty);
+    let mut recorder = match &config.record {
+        Some(path) => Some(Recorder::create(
+            path,
+            seed,
+            config.variant,
+            config.difficulty,
+        )?),
+        None => None,
+    };
+    let mut hints = config.hints.map(Hints::new);
+    if let Some(hints) = &hints {
+        println!(
+            "{}",
+            messages.format_count(MessageId::HintsAvailable, hints.remaining().into(), &[])
+        );
+    }
+    let started = Instant::now();
+
+    loop {
+        println!("{}", messages.get(MessageId::PleaseInput));
+
+        let line = read_line()?;
+
+        // Spending a hint doesn't touch the game itself, so it isn't a
+        // turn and never shows up in a session log either
+        if let (Some(hints), Some(kind)) = (&mut hints, hints::requested(&line)) {
+            let game = game.as_number_game().expect("hints need the number game");
+            println!("{}", spend_hint(messages, hints, game, kind));
+            continue;
+        }
+
+        let result = game.play(&line);
+        if let Some(recorder) = &mut recorder {
+            recorder.record(&line, &result)?;
+        }
+        let guess = line.trim();
+
+        let feedback = match result {
+            Ok(feedback) => feedback,
+            Err(InvalidGuess::Number(ParseGuessError::NotANumber)) => continue,
+            Err(err) => {
+                let reason = explain(messages, &err);
+                println!(
+                    "{}",
+                    messages.format(MessageId::Sorry, &[("reason", &reason)])
+                );
+                continue;
+            }
+        };
+
+        println!(
+            "{}",
+            messages.format(MessageId::YouGuessed, &[("guess", &guess)])
+        );
+
+        if feedback.is_win() {
+            println!("{}", messages.get(MessageId::YouWin));
+            println!(
+                "{}",
+                messages.format_count(MessageId::TookAttempts, game.attempts().into(), &[])
             );
+            break;
+        }
+        println!("{}", describe(messages, guess, &feedback));
+
+        if let (Some(hints), Some(game), Ok(guess)) =
+            (&mut hints, game.as_number_game(), guess.parse::<Guess>())
+        {
+            println!("{}", proximity(messages, hints.after_guess(game, &guess)));
         }
-        Guess { value }
     }
-    pub fn value(&self) -> i32 {
-        self.value
+
+    // Difficulty only really means something for the number game, so
+    // that's the only one with a leaderboard
+    if config.variant != VariantKind::Number {
+        return Ok(());
     }
-}
 
-// impl std::cmp::Ord for Guess {
-//     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-//         if self.value < other.value {
-//             Ordering::Less
-//         } else if self.value > other.value {
-//             Ordering::Greater
-//         } else {
-//             Ordering::Equal
-//         }
-//     }
-// }
-
-// impl std::cmp::PartialOrd for Guess {
-//     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-//         if self.value < other.value {
-//             Some(Ordering::Less)
-//         } else if self.value > other.value {
-//             Some(Ordering::Greater)
-//         } else {
-//             Some(Ordering::Equal)
-//         }
-//     }
-// }
-
-// impl std::cmp::Eq for Guess {
-//     fn assert_receiver_is_total_eq(&self) {}
-// }
-
-// impl std::cmp::PartialEq for Guess {
-//     fn eq(&self, other: &Self) -> bool {
-//         self.value == other.value
-//     }
-// }
+    let elapsed = started.elapsed();
+    let player = match &config.player {
+        Some(player) => player.clone(),
+        None => {
+            println!("{}", messages.get(MessageId::EnterName));
+            read_line()?.trim().to_string()
+        }
+    };
 
-fn main() {
-    println!("Guess the number!");
+    let mut leaderboard = Leaderboard::load(&config.scores_file)?;
+    leaderboard.record(Score {
+        player,
+        difficulty: config.difficulty,
+        attempts: game.attempts(),
+        elapsed,
+    });
+    leaderboard.save()?;
 
-    let secret_number = Guess::new(rand::thread_rng().gen_range(1..=100));
+    Ok(())
+}
 
-    loop {
-        println!("Please input your guess.");
+fn describe(messages: &Catalogue, guess: &str, feedback: &Feedback) -> String {
+    match feedback {
+        Feedback::Number(Outcome::TooSmall) => messages.get(MessageId::TooSmall),
+        Feedback::Number(Outcome::TooBig) => messages.get(MessageId::TooBig),
+        Feedback::Number(Outcome::Win) => messages.get(MessageId::YouWin),
+        Feedback::BullsAndCows { bulls, cows, .. } => {
+            messages.format(MessageId::BullsAndCows, &[("bulls", bulls), ("cows", cows)])
+        }
+        // Uppercase means right letter in the right place, lowercase
+        // means the letter is somewhere else in the word
+        Feedback::Word(marks) => guess
+            .to_lowercase()
+            .chars()
+            .zip(marks)
+            .map(|(letter, mark)| match mark {
+                Mark::Correct => letter.to_ascii_uppercase(),
+                Mark::Present => letter,
+                Mark::Absent => '_',
+            })
+            .collect(),
+    }
+}
 
-        let mut guess = String::new();
+fn proximity(messages: &Catalogue, proximity: Proximity) -> String {
+    let distance = messages.get(match proximity.distance {
+        Distance::Boiling => MessageId::Boiling,
+        Distance::Hot => MessageId::Hot,
+        Distance::Warm => MessageId::Warm,
+        Distance::Cold => MessageId::Cold,
+    });
+    match proximity.temperature {
+        Some(temperature) => {
+            let temperature = messages.get(match temperature {
+                Temperature::Warmer => MessageId::Warmer,
+                Temperature::Colder => MessageId::Colder,
+                Temperature::Same => MessageId::SameDistance,
+            });
+            format!("{} {}", temperature, distance)
+        }
+        None => distance,
+    }
+}
 
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line!");
+fn spend_hint(messages: &Catalogue, hints: &mut Hints, game: &Game, kind: &str) -> String {
+    let kind: ClueKind = match kind.parse() {
+        Ok(kind) => kind,
+        Err(_) => return messages.get(MessageId::UnknownHint),
+    };
+    let clue = match hints.spend(game, kind) {
+        Ok(clue) => clue,
+        Err(NoHintsLeft) => return messages.get(MessageId::NoHintsLeft),
+    };
+    let clue = match clue {
+        Clue::Even => messages.get(MessageId::ClueEven),
+        Clue::Odd => messages.get(MessageId::ClueOdd),
+        Clue::Quarter { low, high, .. } => {
+            messages.format(MessageId::ClueQuarter, &[("low", &low), ("high", &high)])
+        }
+    };
+    let left = messages.format_count(MessageId::HintsLeft, hints.remaining().into(), &[]);
+    format!("{} {}", clue, left)
+}
 
-        let guess: Guess = match guess.trim().parse() {
-            Ok(num) => Guess::new(num),
-            Err(_) => continue,
-        };
+fn explain(messages: &Catalogue, err: &InvalidGuess) -> String {
+    match err {
+        InvalidGuess::Number(ParseGuessError::NotANumber) => messages.get(MessageId::NotANumber),
+        InvalidGuess::Number(ParseGuessError::OutOfRange(value)) => messages.format(
+            MessageId::OutOfRange,
+            &[("value", value), ("min", &MIN_GUESS), ("max", &MAX_GUESS)],
+        ),
+        InvalidGuess::WrongLength { expected } => {
+            messages.format_count(MessageId::WrongLength, *expected as u64, &[])
+        }
+        InvalidGuess::NotDigits => messages.get(MessageId::NotDigits),
+        InvalidGuess::RepeatedDigits => messages.get(MessageId::RepeatedDigits),
+        InvalidGuess::NotLetters => messages.get(MessageId::NotLetters),
+    }
+}
 
-        println!("You guessed: {}", guess.value());
-
-        // 17th Jan:
-        // Ooft, "guess" of type Guess is a custom type
-        // and it dosen't look like we know how to compare two
-        // instances of a Guess...
-        // We will fix this later...with TRAITS!
-
-        // 19th Jan:
-        // Have a look at the updated implementation
-        // above where we have defined how to compare instances
-        // of Guess. We have implemented the relevant traits on the type.
-
-        // 22nd Jan:
-        // Another update! This time, we are deriving the
-        // traits for PartialEq, Eq, PartialOrd, Ord from the standard
-        // library which does the trick!
-
-        // Hence, we should derive behaviour from the base traits (as
-        // it often suffices). However, if we wish to change the default
-        // behaviour / implementation of these traits, we can override them
-        // explicitly like we did before.
-
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
-            }
+fn show_scores(config: &Config, messages: &Catalogue, top: usize) -> Result<(), Box<dyn Error>> {
+    let leaderboard = Leaderboard::load(&config.scores_file)?;
+    for difficulty in Difficulty::ALL {
+        println!(
+            "{}",
+            messages.format(MessageId::ScoresHeader, &[("difficulty", &difficulty)])
+        );
+        for (rank, score) in leaderboard.top(difficulty, top).iter().enumerate() {
+            let seconds = format!("{:.1}", score.elapsed.as_secs_f64());
+            println!(
+                "{}",
+                messages.format_count(
+                    MessageId::ScoreLine,
+                    score.attempts.into(),
+                    &[
+                        ("rank", &(rank + 1)),
+                        ("player", &score.player),
+                        ("seconds", &seconds),
+                    ],
+                )
+            );
         }
     }
+    Ok(())
+}
+
+fn auto_play(config: &Config, messages: &Catalogue, runs: u32) {
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let stats = solver::auto_play(config.difficulty, runs, seed);
+    println!(
+        "{}",
+        messages.format_count(
+            MessageId::SolverPlayed,
+            stats.runs.into(),
+            &[("difficulty", &config.difficulty), ("seed", &seed)],
+        )
+    );
+    let average = format!("{:.2}", stats.average);
+    println!(
+        "{}",
+        messages.format(MessageId::SolverAverage, &[("average", &average)])
+    );
+    let verdict = messages.get(if stats.within_bound() {
+        MessageId::WithinBound
+    } else {
+        MessageId::BoundExceeded
+    });
+    println!(
+        "{}",
+        messages.format(
+            MessageId::SolverWorst,
+            &[
+                ("worst", &stats.worst),
+                ("bound", &stats.bound),
+                ("verdict", &verdict),
+            ],
+        )
+    );
+}
+
+fn serve(config: &Config, messages: &Catalogue, addr: &str) -> Result<(), Box<dyn Error>> {
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let server = Server::bind(addr, config.difficulty, seed)?;
+    println!(
+        "{}",
+        messages.format(
+            MessageId::Serving,
+            &[
+                ("difficulty", &config.difficulty),
+                ("addr", &server.local_addr()?),
+            ],
+        )
+    );
+    server.run()?;
+    Ok(())
 }
 
-// Unit tests in Rust go in the same file as the code
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    // The #[should_panic] annotation asserts if the test
-    // causes a panic
-    #[should_panic]
-    fn greater_than_100() {
-        Guess::new(200);
+fn replay(messages: &Catalogue, path: &Path) -> Result<(), Box<dyn Error>> {
+    let session = Session::load(path)?;
+    match session.replay() {
+        Ok(turns) => {
+            println!(
+                "{}",
+                messages.format_count(
+                    MessageId::Replayed,
+                    turns as u64,
+                    &[("variant", &session.variant), ("seed", &session.seed)],
+                )
+            );
+            Ok(())
+        }
+        Err(mismatch) => Err(messages
+            .format(MessageId::ReplayDiverged, &[("mismatch", &mismatch)])
+            .into()),
     }
+}
 
-    #[test]
-    // The "expected" parameter takes in a substring of the error
-    // message that should be present in the panic!()
-    // The below test panics with an error message containing the
-    // expected substring - thus it passes! If the error message
-    // is incorrect, this test will fail.
-
-    // THIS IS A REALLY GOOD WAY OF MAKING SURE THAT A TEST
-    // PANICS FOR THE "RIGHT / EXPECTED" REASON!
-    #[should_panic(expected = "less than or equal to 100")]
-    fn greater_than_100_with_expected() {
-        Guess::new(200);
+// Only the line ending is stripped so session logs see exactly what was typed
+fn read_line() -> io::Result<String> {
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "no more input",
+        ));
     }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
 }
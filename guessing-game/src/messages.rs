@@ -0,0 +1,617 @@
+// Every player-facing string lives here, keyed by a MessageId, with one
+// catalogue per language. Placeholders like {guess} are filled in by
+// name, and messages that mention a count carry one text per plural
+// form so "1 attempt" and "2 attempts" both read naturally.
+//
+// The error types in the library keep their English Display output on
+// purpose: session logs and the TCP protocol rely on it staying stable.
+
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageId {
+    GuessTheNumber,
+    GuessTheDigits,
+    GuessTheWord,
+    PleaseInput,
+    YouGuessed,
+    TooSmall,
+    TooBig,
+    BullsAndCows,
+    YouWin,
+    TookAttempts,
+    Sorry,
+    NotANumber,
+    OutOfRange,
+    WrongLength,
+    NotDigits,
+    RepeatedDigits,
+    NotLetters,
+    EnterName,
+    ScoreLine,
+    Warmer,
+    Colder,
+    SameDistance,
+    Boiling,
+    Hot,
+    Warm,
+    Cold,
+    ClueEven,
+    ClueOdd,
+    ClueQuarter,
+    HintsAvailable,
+    HintsLeft,
+    NoHintsLeft,
+    UnknownHint,
+    ArgumentsError,
+    BadSeed,
+    UnexpectedArgument,
+    HintsNeedNumbers,
+    NeedsValue,
+    UnknownDifficulty,
+    UnknownVariant,
+    UnsupportedLanguage,
+    ApplicationError,
+    GuessTooLow,
+    GuessTooHigh,
+    ScoresHeader,
+    SolverPlayed,
+    SolverAverage,
+    SolverWorst,
+    WithinBound,
+    BoundExceeded,
+    Serving,
+    Replayed,
+    ReplayDiverged,
+}
+
+impl MessageId {
+    pub const ALL: [MessageId; 53] = [
+        MessageId::GuessTheNumber,
+        MessageId::GuessTheDigits,
+        MessageId::GuessTheWord,
+        MessageId::PleaseInput,
+        MessageId::YouGuessed,
+        MessageId::TooSmall,
+        MessageId::TooBig,
+        MessageId::BullsAndCows,
+        MessageId::YouWin,
+        MessageId::TookAttempts,
+        MessageId::Sorry,
+        MessageId::NotANumber,
+        MessageId::OutOfRange,
+        MessageId::WrongLength,
+        MessageId::NotDigits,
+        MessageId::RepeatedDigits,
+        MessageId::NotLetters,
+        MessageId::EnterName,
+        MessageId::ScoreLine,
+        MessageId::Warmer,
+        MessageId::Colder,
+        MessageId::SameDistance,
+        MessageId::Boiling,
+        MessageId::Hot,
+        MessageId::Warm,
+        MessageId::Cold,
+        MessageId::ClueEven,
+        MessageId::ClueOdd,
+        MessageId::ClueQuarter,
+        MessageId::HintsAvailable,
+        MessageId::HintsLeft,
+        MessageId::NoHintsLeft,
+        MessageId::UnknownHint,
+        MessageId::ArgumentsError,
+        MessageId::BadSeed,
+        MessageId::UnexpectedArgument,
+        MessageId::HintsNeedNumbers,
+        MessageId::NeedsValue,
+        MessageId::UnknownDifficulty,
+        MessageId::UnknownVariant,
+        MessageId::UnsupportedLanguage,
+        MessageId::ApplicationError,
+        MessageId::GuessTooLow,
+        MessageId::GuessTooHigh,
+        MessageId::ScoresHeader,
+        MessageId::SolverPlayed,
+        MessageId::SolverAverage,
+        MessageId::SolverWorst,
+        MessageId::WithinBound,
+        MessageId::BoundExceeded,
+        MessageId::Serving,
+        MessageId::Replayed,
+        MessageId::ReplayDiverged,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    // Understands plain codes ("fr") as well as LANG style values
+    // ("fr_FR.UTF-8"). Anything else is None.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::English),
+            "fr" => Some(Locale::French),
+            _ => None,
+        }
+    }
+
+    // Which plural form to use for a count. English only uses the
+    // singular for exactly one; French uses it for zero as well.
+    fn plural_form(&self, count: u64) -> usize {
+        let singular = match self {
+            Locale::English => count == 1,
+            Locale::French => count <= 1,
+        };
+        if singular {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn entries(&self) -> &'static [(MessageId, &'static [&'static str])] {
+        match self {
+            Locale::English => ENGLISH,
+            Locale::French => FRENCH,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Locale, String> {
+        Locale::from_tag(s).ok_or_else(|| format!("unsupported language '{}'", s))
+    }
+}
+
+const ENGLISH: &[(MessageId, &[&str])] = &[
+    (MessageId::GuessTheNumber, &["Guess the number!"]),
+    (
+        MessageId::GuessTheDigits,
+        &["Guess the digits - every digit is different!"],
+    ),
+    (MessageId::GuessTheWord, &["Guess the five letter word!"]),
+    (MessageId::PleaseInput, &["Please input your guess."]),
+    (MessageId::YouGuessed, &["You guessed: {guess}"]),
+    (MessageId::TooSmall, &["Too small!"]),
+    (MessageId::TooBig, &["Too big!"]),
+    (MessageId::BullsAndCows, &["Bulls: {bulls}, cows: {cows}"]),
+    (MessageId::YouWin, &["You win!"]),
+    (
+        MessageId::TookAttempts,
+        &[
+            "It took you {count} attempt.",
+            "It took you {count} attempts.",
+        ],
+    ),
+    (MessageId::Sorry, &["Sorry, {reason}."]),
+    (MessageId::NotANumber, &["that is not a number"]),
+    (
+        MessageId::OutOfRange,
+        &["{value} is not between {min} and {max}"],
+    ),
+    (
+        MessageId::WrongLength,
+        &[
+            "your guess needs exactly {count} character",
+            "your guess needs exactly {count} characters",
+        ],
+    ),
+    (MessageId::NotDigits, &["only the digits 0-9 are allowed"]),
+    (
+        MessageId::RepeatedDigits,
+        &["every digit must be different"],
+    ),
+    (MessageId::NotLetters, &["only the letters a-z are allowed"]),
+    (
+        MessageId::EnterName,
+        &["Enter your name for the leaderboard:"],
+    ),
+    (
+        MessageId::ScoreLine,
+        &[
+            "{rank}. {player} - {count} attempt in {seconds}s",
+            "{rank}. {player} - {count} attempts in {seconds}s",
+        ],
+    ),
+    (MessageId::Warmer, &["Warmer than last time."]),
+    (MessageId::Colder, &["Colder than last time."]),
+    (MessageId::SameDistance, &["Just as close as last time."]),
+    (MessageId::Boiling, &["You're boiling!"]),
+    (MessageId::Hot, &["You're hot."]),
+    (MessageId::Warm, &["You're warm."]),
+    (MessageId::Cold, &["You're cold."]),
+    (MessageId::ClueEven, &["The number is even."]),
+    (MessageId::ClueOdd, &["The number is odd."]),
+    (
+        MessageId::ClueQuarter,
+        &["The number is between {low} and {high}."],
+    ),
+    (
+        MessageId::HintsAvailable,
+        &[
+            "You have {count} hint. Type 'hint parity' or 'hint quarter' to use it.",
+            "You have {count} hints. Type 'hint parity' or 'hint quarter' to use one.",
+        ],
+    ),
+    (
+        MessageId::HintsLeft,
+        &["{count} hint left.", "{count} hints left."],
+    ),
+    (MessageId::NoHintsLeft, &["You have no hints left."]),
+    (
+        MessageId::UnknownHint,
+        &["Unknown hint - try 'hint parity' or 'hint quarter'."],
+    ),
+    (
+        MessageId::ArgumentsError,
+        &["Problem parsing arguments: {error}"],
+    ),
+    (MessageId::BadSeed, &["bad seed '{seed}'"]),
+    (
+        MessageId::UnexpectedArgument,
+        &["unexpected argument '{argument}'"],
+    ),
+    (
+        MessageId::HintsNeedNumbers,
+        &["--hints only works with the number variant"],
+    ),
+    (MessageId::NeedsValue, &["{flag} needs a value"]),
+    (
+        MessageId::UnknownDifficulty,
+        &["unknown difficulty '{difficulty}'"],
+    ),
+    (MessageId::UnknownVariant, &["unknown variant '{variant}'"]),
+    (
+        MessageId::UnsupportedLanguage,
+        &["unsupported language '{lang}'"],
+    ),
+    (MessageId::ApplicationError, &["Application error: {error}"]),
+    (
+        MessageId::GuessTooLow,
+        &["Guess value must be greater than or equal to {min}, got {value} instead."],
+    ),
+    (
+        MessageId::GuessTooHigh,
+        &["Guess value must be less than or equal to {max}, got {value} instead."],
+    ),
+    (MessageId::ScoresHeader, &["== {difficulty} =="]),
+    (
+        MessageId::SolverPlayed,
+        &[
+            "Solver played {count} {difficulty} game (seed {seed})",
+            "Solver played {count} {difficulty} games (seed {seed})",
+        ],
+    ),
+    (MessageId::SolverAverage, &["Average guesses: {average}"]),
+    (
+        MessageId::SolverWorst,
+        &["Worst case: {worst} (bound {bound}) - {verdict}"],
+    ),
+    (MessageId::WithinBound, &["within bound"]),
+    (MessageId::BoundExceeded, &["BOUND EXCEEDED"]),
+    (
+        MessageId::Serving,
+        &["Serving {difficulty} games on {addr}"],
+    ),
+    (
+        MessageId::Replayed,
+        &[
+            "Replayed {count} turn of a {variant} game (seed {seed}): every hint matches",
+            "Replayed {count} turns of a {variant} game (seed {seed}): every hint matches",
+        ],
+    ),
+    (
+        MessageId::ReplayDiverged,
+        &["replay diverged at {mismatch}"],
+    ),
+];
+
+const FRENCH: &[(MessageId, &[&str])] = &[
+    (MessageId::GuessTheNumber, &["Devinez le nombre !"]),
+    (
+        MessageId::GuessTheDigits,
+        &["Devinez les chiffres - ils sont tous différents !"],
+    ),
+    (
+        MessageId::GuessTheWord,
+        &["Devinez le mot de cinq lettres !"],
+    ),
+    (
+        MessageId::PleaseInput,
+        &["Veuillez saisir votre proposition."],
+    ),
+    (MessageId::YouGuessed, &["Vous avez proposé : {guess}"]),
+    (MessageId::TooSmall, &["Trop petit !"]),
+    (MessageId::TooBig, &["Trop grand !"]),
+    (
+        MessageId::BullsAndCows,
+        &["Taureaux : {bulls}, vaches : {cows}"],
+    ),
+    (MessageId::YouWin, &["Vous avez gagné !"]),
+    (
+        MessageId::TookAttempts,
+        &[
+            "Il vous a fallu {count} essai.",
+            "Il vous a fallu {count} essais.",
+        ],
+    ),
+    (MessageId::Sorry, &["Désolé, {reason}."]),
+    (MessageId::NotANumber, &["ce n'est pas un nombre"]),
+    (
+        MessageId::OutOfRange,
+        &["{value} n'est pas entre {min} et {max}"],
+    ),
+    (
+        MessageId::WrongLength,
+        &[
+            "votre proposition doit faire exactement {count} caractère",
+            "votre proposition doit faire exactement {count} caractères",
+        ],
+    ),
+    (
+        MessageId::NotDigits,
+        &["seuls les chiffres 0-9 sont autorisés"],
+    ),
+    (
+        MessageId::RepeatedDigits,
+        &["chaque chiffre doit être différent"],
+    ),
+    (
+        MessageId::NotLetters,
+        &["seules les lettres a-z sont autorisées"],
+    ),
+    (
+        MessageId::EnterName,
+        &["Entrez votre nom pour le classement :"],
+    ),
+    (
+        MessageId::ScoreLine,
+        &[
+            "{rank}. {player} - {count} essai en {seconds} s",
+            "{rank}. {player} - {count} essais en {seconds} s",
+        ],
+    ),
+    (MessageId::Warmer, &["Plus chaud que la dernière fois."]),
+    (MessageId::Colder, &["Plus froid que la dernière fois."]),
+    (
+        MessageId::SameDistance,
+        &["Aussi proche que la dernière fois."],
+    ),
+    (MessageId::Boiling, &["Ça brûle !"]),
+    (MessageId::Hot, &["C'est chaud."]),
+    (MessageId::Warm, &["C'est tiède."]),
+    (MessageId::Cold, &["C'est froid."]),
+    (MessageId::ClueEven, &["Le nombre est pair."]),
+    (MessageId::ClueOdd, &["Le nombre est impair."]),
+    (
+        MessageId::ClueQuarter,
+        &["Le nombre est entre {low} et {high}."],
+    ),
+    (
+        MessageId::HintsAvailable,
+        &[
+            "Vous avez {count} indice. Tapez 'hint parity' ou 'hint quarter' pour l'utiliser.",
+            "Vous avez {count} indices. Tapez 'hint parity' ou 'hint quarter' pour en utiliser un.",
+        ],
+    ),
+    (
+        MessageId::HintsLeft,
+        &[
+            "Il vous reste {count} indice.",
+            "Il vous reste {count} indices.",
+        ],
+    ),
+    (MessageId::NoHintsLeft, &["Vous n'avez plus d'indices."]),
+    (
+        MessageId::UnknownHint,
+        &["Indice inconnu - essayez 'hint parity' ou 'hint quarter'."],
+    ),
+    (
+        MessageId::ArgumentsError,
+        &["Problème dans les arguments : {error}"],
+    ),
+    (MessageId::BadSeed, &["graine invalide '{seed}'"]),
+    (
+        MessageId::UnexpectedArgument,
+        &["argument inattendu '{argument}'"],
+    ),
+    (
+        MessageId::HintsNeedNumbers,
+        &["--hints ne marche qu'avec la variante des nombres"],
+    ),
+    (MessageId::NeedsValue, &["{flag} attend une valeur"]),
+    (
+        MessageId::UnknownDifficulty,
+        &["difficulté inconnue '{difficulty}'"],
+    ),
+    (MessageId::UnknownVariant, &["variante inconnue '{variant}'"]),
+    (
+        MessageId::UnsupportedLanguage,
+        &["langue non prise en charge '{lang}'"],
+    ),
+    (MessageId::ApplicationError, &["Erreur de l'application : {error}"]),
+    (
+        MessageId::GuessTooLow,
+        &["La proposition doit être supérieure ou égale à {min}, reçu {value}."],
+    ),
+    (
+        MessageId::GuessTooHigh,
+        &["La proposition doit être inférieure ou égale à {max}, reçu {value}."],
+    ),
+    (MessageId::ScoresHeader, &["== {difficulty} =="]),
+    (
+        MessageId::SolverPlayed,
+        &[
+            "Le solveur a joué {count} partie {difficulty} (graine {seed})",
+            "Le solveur a joué {count} parties {difficulty} (graine {seed})",
+        ],
+    ),
+    (
+        MessageId::SolverAverage,
+        &["Nombre moyen de propositions : {average}"],
+    ),
+    (
+        MessageId::SolverWorst,
+        &["Pire cas : {worst} (borne {bound}) - {verdict}"],
+    ),
+    (MessageId::WithinBound, &["borne respectée"]),
+    (MessageId::BoundExceeded, &["BORNE DÉPASSÉE"]),
+    (
+        MessageId::Serving,
+        &["Parties {difficulty} servies sur {addr}"],
+    ),
+    (
+        MessageId::Replayed,
+        &[
+            "{count} tour rejoué d'une partie {variant} (graine {seed}) : tous les indices concordent",
+            "{count} tours rejoués d'une partie {variant} (graine {seed}) : tous les indices concordent",
+        ],
+    ),
+    (MessageId::ReplayDiverged, &["la rediffusion diverge à {mismatch}"]),
+];
+
+pub struct Catalogue {
+    locale: Locale,
+    messages: HashMap<MessageId, &'static [&'static str]>,
+}
+
+impl Catalogue {
+    // Anything a translation is missing falls back to English
+    pub fn new(locale: Locale) -> Catalogue {
+        let mut messages: HashMap<_, _> = ENGLISH.iter().copied().collect();
+        messages.extend(locale.entries().iter().copied());
+        Catalogue { locale, messages }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn get(&self, id: MessageId) -> String {
+        self.format(id, &[])
+    }
+
+    pub fn format(&self, id: MessageId, args: &[(&str, &dyn Display)]) -> String {
+        fill(self.messages[&id][0], args)
+    }
+
+    // Picks the right plural form for `count` and fills in {count} too
+    pub fn format_count(&self, id: MessageId, count: u64, args: &[(&str, &dyn Display)]) -> String {
+        let forms = self.messages[&id];
+        let form = self.locale.plural_form(count).min(forms.len() - 1);
+        let mut all: Vec<(&str, &dyn Display)> = vec![("count", &count)];
+        all.extend_from_slice(args);
+        fill(forms[form], &all)
+    }
+}
+
+// One pass over the template, so whatever goes into a placeholder is
+// never looked at again: a player called "{count}" stays "{count}".
+// Placeholders nobody gave a value for are left as they are.
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((close, value))
+        });
+        match value {
+            Some((close, value)) => {
+                text.push_str(&value.to_string());
+                rest = &after[close + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_locale_has_every_message() {
+        for locale in [Locale::English, Locale::French] {
+            for id in MessageId::ALL {
+                assert!(
+                    locale.entries().iter().any(|(entry, _)| *entry == id),
+                    "{:?} is missing {:?}",
+                    locale,
+                    id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn locale_comes_from_lang_style_tags() {
+        assert_eq!(Some(Locale::French), Locale::from_tag("fr_FR.UTF-8"));
+        assert_eq!(Some(Locale::English), Locale::from_tag("en-GB"));
+        assert_eq!(Some(Locale::English), Locale::from_tag("C"));
+        assert_eq!(None, Locale::from_tag("tlh"));
+        assert!("de".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn placeholders_are_filled_by_name() {
+        let catalogue = Catalogue::new(Locale::English);
+        assert_eq!("Too small!", catalogue.get(MessageId::TooSmall));
+        assert_eq!(
+            "Bulls: 2, cows: 1",
+            catalogue.format(MessageId::BullsAndCows, &[("cows", &1), ("bulls", &2)])
+        );
+    }
+
+    #[test]
+    fn filled_in_text_is_not_filled_in_again() {
+        assert_eq!(
+            "{seconds} took 3s, {nobody}",
+            fill(
+                "{name} took {seconds}s, {nobody}",
+                &[("name", &"{seconds}"), ("seconds", &3)]
+            )
+        );
+        let catalogue = Catalogue::new(Locale::English);
+        assert_eq!(
+            "It took you 2 attempts.",
+            catalogue.format_count(MessageId::TookAttempts, 2, &[("name", &"{count}")])
+        );
+    }
+
+    #[test]
+    fn plurals_follow_the_locale() {
+        let english = Catalogue::new(Locale::English);
+        let french = Catalogue::new(Locale::French);
+        let took =
+            |catalogue: &Catalogue, n| catalogue.format_count(MessageId::TookAttempts, n, &[]);
+
+        assert_eq!("It took you 1 attempt.", took(&english, 1));
+        assert_eq!("It took you 0 attempts.", took(&english, 0));
+        assert_eq!("It took you 7 attempts.", took(&english, 7));
+        assert_eq!("Il vous a fallu 1 essai.", took(&french, 1));
+        assert_eq!("Il vous a fallu 0 essai.", took(&french, 0));
+        assert_eq!("Il vous a fallu 7 essais.", took(&french, 7));
+    }
+}
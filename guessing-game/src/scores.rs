@@ -0,0 +1,211 @@
+use crate::Difficulty;
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub player: String,
+    pub difficulty: Difficulty,
+    pub attempts: u32,
+    pub elapsed: Duration,
+}
+
+impl Score {
+    // One score per line, tab separated. The player name goes last so
+    // it is the only field that could ever contain odd characters.
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.difficulty,
+            self.attempts,
+            self.elapsed.as_millis(),
+            self.player
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Score, String> {
+        let mut fields = line.splitn(4, '\t');
+        let mut next = |name| {
+            fields
+                .next()
+                .ok_or(format!("missing {} in '{}'", name, line))
+        };
+
+        let difficulty = next("difficulty")?.parse()?;
+        let attempts = next("attempts")?
+            .parse()
+            .map_err(|_| format!("bad attempt count in '{}'", line))?;
+        let elapsed = next("elapsed time")?
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| format!("bad elapsed time in '{}'", line))?;
+        let player = next("player")?.to_string();
+
+        Ok(Score {
+            player,
+            difficulty,
+            attempts,
+            elapsed,
+        })
+    }
+}
+
+pub struct Leaderboard {
+    path: PathBuf,
+    scores: Vec<Score>,
+}
+
+impl Leaderboard {
+    // A missing file just means nobody has won yet
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Leaderboard> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let scores = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Score::from_line)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+        Ok(Leaderboard { path, scores })
+    }
+
+    pub fn record(&mut self, mut score: Score) {
+        // Tabs and newlines would break the file format
+        score.player = score
+            .player
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect::<String>()
+            .trim()
+            .to_string();
+        if score.player.is_empty() {
+            score.player = String::from("anonymous");
+        }
+        self.scores.push(score);
+    }
+
+    // Fewest attempts wins, ties are broken by the faster time
+    pub fn top(&self, difficulty: Difficulty, n: usize) -> Vec<&Score> {
+        let mut scores: Vec<&Score> = self
+            .scores
+            .iter()
+            .filter(|score| score.difficulty == difficulty)
+            .collect();
+        scores.sort_by_key(|score| (score.attempts, score.elapsed));
+        scores.truncate(n);
+        scores
+    }
+
+    // The new contents are written to a temporary file next to the real
+    // one and then renamed over it. A rename within one directory is
+    // atomic, so a crash mid-save leaves either the old board or the new
+    // one on disk - never half of each.
+    pub fn save(&self) -> io::Result<()> {
+        let tmp_path = self.tmp_path();
+        let mut file = fs::File::create(&tmp_path)?;
+        for score in &self.scores {
+            writeln!(file, "{}", score.to_line())?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        self.path.with_file_name(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn scratch_file(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "fsg_guessing_game_{}_{}.txt",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn score(player: &str, difficulty: Difficulty, attempts: u32, millis: u64) -> Score {
+        Score {
+            player: player.to_string(),
+            difficulty,
+            attempts,
+            elapsed: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn missing_file_is_an_empty_board() {
+        let board = Leaderboard::load(scratch_file("missing")).unwrap();
+        assert!(board.top(Difficulty::Hard, 10).is_empty());
+    }
+
+    #[test]
+    fn scores_survive_a_round_trip() {
+        let path = scratch_file("round_trip");
+        let mut board = Leaderboard::load(&path).unwrap();
+        board.record(score("Ferris the\tcrab", Difficulty::Hard, 5, 1200));
+        board.record(score("Bob", Difficulty::Easy, 2, 300));
+        board.save().unwrap();
+
+        let board = Leaderboard::load(&path).unwrap();
+        assert_eq!(
+            vec![&score("Ferris the crab", Difficulty::Hard, 5, 1200)],
+            board.top(Difficulty::Hard, 10)
+        );
+        assert_eq!(1, board.top(Difficulty::Easy, 10).len());
+        assert!(!board.tmp_path().exists());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn top_is_sorted_and_truncated() {
+        let mut board = Leaderboard::load(scratch_file("top")).unwrap();
+        board.record(score("slow", Difficulty::Hard, 4, 9000));
+        board.record(score("fast", Difficulty::Hard, 4, 1000));
+        board.record(score("best", Difficulty::Hard, 3, 5000));
+        board.record(score("worst", Difficulty::Hard, 9, 1));
+        let names: Vec<&str> = board
+            .top(Difficulty::Hard, 3)
+            .iter()
+            .map(|score| score.player.as_str())
+            .collect();
+        assert_eq!(vec!["best", "fast", "slow"], names);
+    }
+
+    #[test]
+    fn leftover_tmp_file_does_not_clobber_the_board() {
+        // Simulate a crash that happened after the temp file was written
+        // but before it was renamed into place
+        let path = scratch_file("crash");
+        let mut board = Leaderboard::load(&path).unwrap();
+        board.record(score("Alice", Difficulty::Medium, 4, 700));
+        board.save().unwrap();
+        fs::write(board.tmp_path(), "half a li").unwrap();
+
+        let board = Leaderboard::load(&path).unwrap();
+        assert_eq!(1, board.top(Difficulty::Medium, 10).len());
+
+        // ...and the next save replaces the stale temp file
+        board.save().unwrap();
+        assert!(!board.tmp_path().exists());
+        fs::remove_file(path).unwrap();
+    }
+}
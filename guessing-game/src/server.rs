@@ -0,0 +1,371 @@
+// A small multiplayer server. Everyone connected guesses the same secret
+// and sees everybody else's hints; the first correct guess wins the
+// round and a fresh secret is picked.
+//
+// The protocol is one line per message, so `nc localhost 7878` is a
+// perfectly good client.
+//
+//   client -> server         server -> client(s)
+//   NAME <name>              WELCOME <id>                (to the new player)
+//   GUESS <n>  (or just n)   ROUND <round> <low> <high>  (to the new player / everyone)
+//   QUIT                     HINT <name> <n> TOO_SMALL   (everyone)
+//                            HINT <name> <n> TOO_BIG     (everyone)
+//                            WIN <name> <n> <attempts>   (everyone)
+//                            ERROR <message>             (to the sender)
+
+use crate::{Difficulty, Game, Guess, Outcome};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+// How many lines can wait to go out to one player. A client that falls
+// this far behind has stopped reading and gets disconnected.
+const OUTBOX_LINES: usize = 256;
+
+struct Player {
+    name: String,
+    // Lines waiting to go out. Each player has a thread of their own
+    // doing the actual writing, so a client that stops reading only
+    // holds up itself and never the lock on the game.
+    outbox: SyncSender<String>,
+    // Only used to hang up on them
+    stream: TcpStream,
+}
+
+// However a player leaves the game - quitting, losing the connection or
+// falling too far behind - their connection is closed behind them. That
+// stops both their threads: the reader sees the end of the stream and
+// the writer, even one stuck on a full socket, gets an error.
+impl Drop for Player {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// Takes the player out of the game when handle_player returns, whichever
+// way it returns
+struct Seat {
+    id: usize,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Drop for Seat {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.players.remove(&self.id);
+        }
+    }
+}
+
+struct Shared {
+    players: HashMap<usize, Player>,
+    next_id: usize,
+    game: Game,
+    round: u32,
+    difficulty: Difficulty,
+    rng: StdRng,
+}
+
+impl Shared {
+    fn round_line(&self) -> String {
+        let range = self.difficulty.range();
+        format!("ROUND {} {} {}", self.round, range.start(), range.end())
+    }
+
+    // Players whose connection has gone away, or who have stopped
+    // reading, are dropped on the spot
+    fn broadcast(&mut self, line: &str) {
+        self.players
+            .retain(|_, player| player.outbox.try_send(line.to_string()).is_ok());
+    }
+
+    fn guess(&mut self, id: usize, guess: Guess) {
+        let name = self.players[&id].name.clone();
+        match self.game.guess(&guess) {
+            Outcome::TooSmall => {
+                self.broadcast(&format!("HINT {} {} TOO_SMALL", name, guess.value()));
+            }
+            Outcome::TooBig => {
+                self.broadcast(&format!("HINT {} {} TOO_BIG", name, guess.value()));
+            }
+            Outcome::Win => {
+                let attempts = self.game.attempts();
+                self.broadcast(&format!("WIN {} {} {}", name, guess.value(), attempts));
+                self.game = Game::with_difficulty(&mut self.rng, self.difficulty);
+                self.round += 1;
+                let round = self.round_line();
+                self.broadcast(&round);
+            }
+        }
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, difficulty: Difficulty, seed: u64) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let game = Game::with_difficulty(&mut rng, difficulty);
+        let shared = Shared {
+            players: HashMap::new(),
+            next_id: 1,
+            game,
+            round: 1,
+            difficulty,
+            rng,
+        };
+        Ok(Server {
+            listener,
+            shared: Arc::new(Mutex::new(shared)),
+        })
+    }
+
+    // Handy when binding to port 0 and letting the OS pick
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // Accepts players forever, one thread per connection
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || {
+                // A misbehaving client only takes down its own thread
+                let _ = handle_player(stream, shared);
+            });
+        }
+        Ok(())
+    }
+}
+
+// Writes whatever turns up in the outbox until the player leaves. If the
+// connection breaks the whole socket is shut, so the reading side of
+// handle_player finishes too and the player is removed.
+fn spawn_writer(mut stream: TcpStream) -> SyncSender<String> {
+    let (outbox, lines) = mpsc::sync_channel::<String>(OUTBOX_LINES);
+    thread::spawn(move || {
+        for line in lines {
+            if writeln!(stream, "{}", line).is_err() {
+                let _ = stream.shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+    outbox
+}
+
+fn handle_player(stream: TcpStream, shared: Arc<Mutex<Shared>>) -> io::Result<()> {
+    // Replies to this player go through the outbox as well, so they stay
+    // in order with everything that's broadcast
+    let outbox = spawn_writer(stream.try_clone()?);
+    let reply = |line: String| {
+        outbox
+            .try_send(line)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    };
+    let id = {
+        let mut shared = shared.lock().unwrap();
+        let id = shared.next_id;
+        shared.next_id += 1;
+        reply(format!("WELCOME {}", id))?;
+        reply(shared.round_line())?;
+        shared.players.insert(
+            id,
+            Player {
+                name: format!("player{}", id),
+                outbox: outbox.clone(),
+                stream: stream.try_clone()?,
+            },
+        );
+        id
+    };
+    let _seat = Seat {
+        id,
+        shared: Arc::clone(&shared),
+    };
+
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        let (command, argument) = match line.split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None if line.parse::<i32>().is_ok() => ("GUESS", line),
+            None => (line, ""),
+        };
+
+        let mut shared = shared.lock().unwrap();
+        if !shared.players.contains_key(&id) {
+            break;
+        }
+        match command.to_uppercase().as_str() {
+            "" => {}
+            "NAME" if !argument.is_empty() && !argument.contains(char::is_whitespace) => {
+                shared.players.get_mut(&id).unwrap().name = argument.to_string();
+            }
+            "NAME" => reply(String::from("ERROR names must be a single word"))?,
+            "GUESS" => match argument.parse::<Guess>() {
+                Ok(guess) => shared.guess(id, guess),
+                Err(err) => reply(format!("ERROR {}", err))?,
+            },
+            "QUIT" => break,
+            other => reply(format!("ERROR unknown command '{}'", other))?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use std::{cmp::Ordering, time::Duration};
+
+    // An in-process client talking to a real server over localhost
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(addr: SocketAddr) -> Client {
+            let stream = TcpStream::connect(addr).unwrap();
+            Client {
+                writer: stream.try_clone().unwrap(),
+                reader: BufReader::new(stream),
+            }
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.writer, "{}", line).unwrap();
+        }
+
+        fn recv(&mut self) -> String {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            line.trim().to_string()
+        }
+    }
+
+    fn start(difficulty: Difficulty) -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0", difficulty, 1).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    #[test]
+    fn hints_are_broadcast_and_first_correct_guess_wins() {
+        let addr = start(Difficulty::Hard);
+        let mut alice = Client::connect(addr);
+        assert_eq!("WELCOME 1", alice.recv());
+        assert_eq!("ROUND 1 1 100", alice.recv());
+        let mut bob = Client::connect(addr);
+        assert_eq!("WELCOME 2", bob.recv());
+        assert_eq!("ROUND 1 1 100", bob.recv());
+
+        alice.send("NAME alice");
+        bob.send("NAME bob");
+
+        // Take turns, each steering the shared solver with the hints
+        // that both of them receive
+        let mut solver = Solver::new(Difficulty::Hard.range());
+        let players = [&mut alice, &mut bob];
+        for turn in 0.. {
+            let guess = solver.next_guess().unwrap();
+            let name = if turn % 2 == 0 { "alice" } else { "bob" };
+            players[turn % 2].send(&format!("GUESS {}", guess.value()));
+
+            let line = players[0].recv();
+            assert_eq!(line, players[1].recv());
+            let fields: Vec<&str> = line.split(' ').collect();
+            assert_eq!(name, fields[1]);
+            match fields[0] {
+                "HINT" if fields[3] == "TOO_SMALL" => solver.feedback(&guess, Ordering::Less),
+                "HINT" => solver.feedback(&guess, Ordering::Greater),
+                "WIN" => break,
+                other => panic!("unexpected message {}", other),
+            }
+        }
+
+        assert_eq!("ROUND 2 1 100", alice.recv());
+        assert_eq!("ROUND 2 1 100", bob.recv());
+    }
+
+    #[test]
+    fn bad_input_only_goes_back_to_the_sender() {
+        let addr = start(Difficulty::Easy);
+        let mut alice = Client::connect(addr);
+        alice.recv();
+        alice.recv();
+
+        alice.send("GUESS 500");
+        assert_eq!("ERROR 500 is not between 1 and 100", alice.recv());
+        alice.send("DANCE");
+        assert_eq!("ERROR unknown command 'DANCE'", alice.recv());
+
+        // A bare number is a guess too
+        alice.send("11");
+        assert!(alice.recv().starts_with("HINT player1 11 TOO_BIG"));
+    }
+
+    #[test]
+    fn a_client_that_stops_reading_holds_nobody_up() {
+        let addr = start(Difficulty::Easy);
+        // Never reads a thing, so its socket buffers fill right up
+        let mut stuck = Client::connect(addr);
+        let mut alice = Client::connect(addr);
+        alice.recv();
+        alice.recv();
+        // Fails the test instead of hanging it if the server gets stuck
+        alice
+            .reader
+            .get_ref()
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        // A long name makes for long hints: 20MB of them, more than any
+        // socket buffer holds
+        let name = "a".repeat(10_000);
+        alice.send(&format!("NAME {}", name));
+        // In batches, but small enough ones that alice's own outbox
+        // never fills while she isn't reading
+        for _ in 0..20 {
+            for _ in 0..100 {
+                alice.send("GUESS 11");
+            }
+            for _ in 0..100 {
+                assert!(alice.recv().ends_with(" 11 TOO_BIG"));
+            }
+        }
+
+        // Once it was dropped its connection was closed as well: reading
+        // what got through runs into the end of the stream
+        let stuck = stuck.reader.get_mut();
+        stuck
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        if let Err(err) = io::copy(stuck, &mut io::sink()) {
+            assert!(
+                !matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ),
+                "still connected"
+            );
+        }
+    }
+}
@@ -0,0 +1,318 @@
+// Session logs record everything needed to play a game again: the seed
+// the secret came from, every raw line the player typed, whether it
+// parsed, and the hint that came back. Replaying a log re-runs the same
+// inputs against a freshly seeded game and checks that every hint still
+// matches, which catches regressions in the comparison logic.
+//
+// The file is plain text, one record per line:
+//
+//   seed 42
+//   variant number
+//   difficulty hard
+//   input 50
+//   parse ok
+//   hint too-big
+//   input fifty
+//   parse error that is not a number
+
+use crate::{
+    variants::{Feedback, InvalidGuess, Mark, Variant, VariantKind},
+    Difficulty, Outcome,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    fmt, fs,
+    io::{self, BufWriter, ErrorKind, Write},
+    path::Path,
+};
+
+// A short, stable name for each hint so logs are easy to read and diff
+pub fn hint_code(feedback: &Feedback) -> String {
+    match feedback {
+        Feedback::Number(Outcome::TooSmall) => String::from("too-small"),
+        Feedback::Number(Outcome::TooBig) => String::from("too-big"),
+        Feedback::Number(Outcome::Win) => String::from("win"),
+        Feedback::BullsAndCows { bulls, cows, .. } => format!("bulls={} cows={}", bulls, cows),
+        Feedback::Word(marks) => marks
+            .iter()
+            .map(|mark| match mark {
+                Mark::Correct => 'C',
+                Mark::Present => 'P',
+                Mark::Absent => '.',
+            })
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurnResult {
+    // The line wasn't a valid guess; holds the error message
+    Rejected(String),
+    // The line was a guess; holds its hint_code()
+    Hint(String),
+}
+
+impl TurnResult {
+    pub fn from_play(result: &Result<Feedback, InvalidGuess>) -> TurnResult {
+        match result {
+            Ok(feedback) => TurnResult::Hint(hint_code(feedback)),
+            Err(err) => TurnResult::Rejected(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TurnResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TurnResult::Rejected(err) => write!(f, "parse error {}", err),
+            TurnResult::Hint(hint) => write!(f, "parse ok\nhint {}", hint),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn {
+    pub input: String,
+    pub result: TurnResult,
+}
+
+// Writes the log as the game goes, so even a session that ends in a
+// crash leaves everything up to that point on disk
+pub struct Recorder {
+    writer: BufWriter<fs::File>,
+}
+
+impl Recorder {
+    pub fn create(
+        path: impl AsRef<Path>,
+        seed: u64,
+        variant: VariantKind,
+        difficulty: Difficulty,
+    ) -> io::Result<Recorder> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writeln!(writer, "seed {}", seed)?;
+        writeln!(writer, "variant {}", variant)?;
+        writeln!(writer, "difficulty {}", difficulty)?;
+        writer.flush()?;
+        Ok(Recorder { writer })
+    }
+
+    pub fn record(
+        &mut self,
+        input: &str,
+        result: &Result<Feedback, InvalidGuess>,
+    ) -> io::Result<()> {
+        // Raw input is kept as typed, minus the line ending
+        writeln!(
+            self.writer,
+            "input {}",
+            input.trim_end_matches(['\r', '\n'])
+        )?;
+        writeln!(self.writer, "{}", TurnResult::from_play(result))?;
+        self.writer.flush()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Session {
+    pub seed: u64,
+    pub variant: VariantKind,
+    pub difficulty: Difficulty,
+    pub turns: Vec<Turn>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub turn: usize,
+    pub input: String,
+    pub expected: TurnResult,
+    pub actual: TurnResult,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "turn {} ({:?}): expected '{}', got '{}'",
+            self.turn,
+            self.input,
+            self.expected.to_string().replace('\n', " / "),
+            self.actual.to_string().replace('\n', " / ")
+        )
+    }
+}
+
+impl Session {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Session> {
+        let contents = fs::read_to_string(path)?;
+        Session::parse(&contents).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    pub fn parse(contents: &str) -> Result<Session, String> {
+        let mut lines = contents.lines().enumerate().map(|(n, line)| {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            (n + 1, key, value)
+        });
+        let mut header = |expected: &str| match lines.next() {
+            Some((_, key, value)) if key == expected => Ok(value.to_string()),
+            _ => Err(format!("session log is missing its '{}' line", expected)),
+        };
+
+        let seed = header("seed")?;
+        let seed = seed.parse().map_err(|_| format!("bad seed '{}'", seed))?;
+        let variant = header("variant")?.parse()?;
+        let difficulty = header("difficulty")?.parse()?;
+
+        let mut turns = Vec::new();
+        while let Some((n, key, input)) = lines.next() {
+            if key != "input" {
+                return Err(format!("line {}: expected 'input', found '{}'", n, key));
+            }
+            let result = match lines.next() {
+                Some((_, "parse", "ok")) => match lines.next() {
+                    Some((_, "hint", hint)) => TurnResult::Hint(hint.to_string()),
+                    _ => return Err(format!("line {}: parsed guess has no hint", n)),
+                },
+                Some((_, "parse", rest)) if rest.starts_with("error ") => {
+                    TurnResult::Rejected(rest["error ".len()..].to_string())
+                }
+                _ => return Err(format!("line {}: input has no parse result", n)),
+            };
+            turns.push(Turn {
+                input: input.to_string(),
+                result,
+            });
+        }
+
+        Ok(Session {
+            seed,
+            variant,
+            difficulty,
+            turns,
+        })
+    }
+
+    // Plays every recorded input against a game seeded the same way and
+    // stops at the first turn whose outcome differs from the log
+    pub fn replay(&self) -> Result<usize, Mismatch> {
+        let mut game: Box<dyn Variant> = self
+            .variant
+            .start(&mut StdRng::seed_from_u64(self.seed), self.difficulty);
+        for (i, turn) in self.turns.iter().enumerate() {
+            let actual = TurnResult::from_play(&game.play(&turn.input));
+            if actual != turn.result {
+                return Err(Mismatch {
+                    turn: i + 1,
+                    input: turn.input.clone(),
+                    expected: turn.result.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(self.turns.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use crate::Guess;
+    use std::env;
+
+    // Records a session on disk the same way the CLI does
+    fn record(path: &Path, seed: u64, variant: VariantKind, inputs: &[&str]) {
+        let mut recorder = Recorder::create(path, seed, variant, Difficulty::Hard).unwrap();
+        let mut game = variant.start(&mut StdRng::seed_from_u64(seed), Difficulty::Hard);
+        for input in inputs {
+            recorder.record(input, &game.play(input)).unwrap();
+        }
+    }
+
+    fn scratch_file(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!(
+            "fsg_guessing_game_session_{}_{}.log",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn recorded_session_replays_cleanly() {
+        let path = scratch_file("clean");
+        record(
+            &path,
+            99,
+            VariantKind::Number,
+            &["50", " 25 ", "abc", "500", "75"],
+        );
+
+        let session = Session::load(&path).unwrap();
+        assert_eq!(99, session.seed);
+        assert_eq!(" 25 ", session.turns[1].input);
+        assert_eq!(
+            TurnResult::Rejected(String::from("that is not a number")),
+            session.turns[2].result
+        );
+        assert_eq!(Ok(5), session.replay());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn whole_game_including_the_win_replays() {
+        // Let the solver find the secret so the log ends in a win
+        let mut game = VariantKind::Number.start(&mut StdRng::seed_from_u64(5), Difficulty::Hard);
+        let mut solver = Solver::new(Difficulty::Hard.range());
+        let mut inputs = Vec::new();
+        loop {
+            let guess: Guess = solver.next_guess().unwrap();
+            inputs.push(guess.value().to_string());
+            match game.play(&guess.value().to_string()).unwrap() {
+                Feedback::Number(outcome) if outcome != Outcome::Win => {
+                    solver.feedback(&guess, outcome.ordering())
+                }
+                _ => break,
+            }
+        }
+
+        let path = scratch_file("win");
+        let inputs: Vec<&str> = inputs.iter().map(|s| s.as_str()).collect();
+        record(&path, 5, VariantKind::Number, &inputs);
+        let session = Session::load(&path).unwrap();
+        assert_eq!(
+            Some(&TurnResult::Hint(String::from("win"))),
+            session.turns.last().map(|turn| &turn.result)
+        );
+        assert_eq!(Ok(inputs.len()), session.replay());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn tampered_hint_is_reported() {
+        let log = "seed 1\nvariant number\ndifficulty hard\n\
+                   input 101\nparse error 101 is not between 1 and 100\n\
+                   input 1\nparse ok\nhint too-big\n";
+        let session = Session::parse(log).unwrap();
+        let mismatch = session.replay().unwrap_err();
+        assert_eq!(2, mismatch.turn);
+        assert_eq!(TurnResult::Hint(String::from("too-small")), mismatch.actual);
+    }
+
+    #[test]
+    fn other_variants_replay_too() {
+        let path = scratch_file("word");
+        record(&path, 3, VariantKind::Word, &["crate", "xx", "ghost"]);
+        assert_eq!(Ok(3), Session::load(&path).unwrap().replay());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn malformed_logs_are_rejected() {
+        assert!(Session::parse("").is_err());
+        assert!(Session::parse("seed x\nvariant number\ndifficulty hard\n").is_err());
+        assert!(Session::parse("seed 1\nvariant number\ndifficulty hard\ninput 5\n").is_err());
+        assert!(
+            Session::parse("seed 1\nvariant number\ndifficulty hard\ninput 5\nparse ok\n").is_err()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{Difficulty, Game, Guess, Outcome};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{cmp::Ordering, ops::RangeInclusive};
+
+// Plays the game by binary search. It only ever sees the Ordering that
+// comes out of guess.cmp(&secret_number), exactly like a human would.
+pub struct Solver {
+    low: i32,
+    high: i32,
+}
+
+impl Solver {
+    pub fn new(range: RangeInclusive<i32>) -> Solver {
+        Solver {
+            low: *range.start(),
+            high: *range.end(),
+        }
+    }
+
+    // None means the feedback so far contradicts itself
+    pub fn next_guess(&self) -> Option<Guess> {
+        if self.low > self.high {
+            return None;
+        }
+        Some(Guess::new(self.low + (self.high - self.low) / 2))
+    }
+
+    pub fn feedback(&mut self, guess: &Guess, ordering: Ordering) {
+        match ordering {
+            Ordering::Less => self.low = guess.value() + 1,
+            Ordering::Greater => self.high = guess.value() - 1,
+            Ordering::Equal => {
+                self.low = guess.value();
+                self.high = guess.value();
+            }
+        }
+    }
+}
+
+// The most guesses binary search can need over n candidates, which is
+// ⌈log2(n + 1)⌉. For the game's ranges (10, 50 and 100 numbers) that is
+// the same as ⌈log2(n)⌉.
+pub fn worst_case_bound(range: &RangeInclusive<i32>) -> u32 {
+    let n = (range.end() - range.start() + 1) as u32;
+    u32::BITS - n.leading_zeros()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AutoPlayStats {
+    pub runs: u32,
+    pub average: f64,
+    pub worst: u32,
+    pub bound: u32,
+}
+
+impl AutoPlayStats {
+    pub fn within_bound(&self) -> bool {
+        self.worst <= self.bound
+    }
+}
+
+// Let the solver play `runs` games against secrets drawn from a seeded
+// RNG, so the same seed always produces the same report.
+pub fn auto_play(difficulty: Difficulty, runs: u32, seed: u64) -> AutoPlayStats {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut total = 0;
+    let mut worst = 0;
+
+    for _ in 0..runs {
+        let mut game = Game::with_difficulty(&mut rng, difficulty);
+        let mut solver = Solver::new(difficulty.range());
+        while let Some(guess) = solver.next_guess() {
+            let outcome = game.guess(&guess);
+            if outcome == Outcome::Win {
+                break;
+            }
+            solver.feedback(&guess, outcome.ordering());
+        }
+        total += game.attempts();
+        worst = worst.max(game.attempts());
+    }
+
+    AutoPlayStats {
+        runs,
+        average: if runs == 0 {
+            0.0
+        } else {
+            total as f64 / runs as f64
+        },
+        worst,
+        bound: worst_case_bound(&difficulty.range()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_matches_log2() {
+        assert_eq!(4, worst_case_bound(&(1..=10)));
+        assert_eq!(6, worst_case_bound(&(1..=50)));
+        assert_eq!(7, worst_case_bound(&(1..=100)));
+        assert_eq!(1, worst_case_bound(&(1..=1)));
+    }
+
+    #[test]
+    fn solver_finds_every_secret_within_bound() {
+        // Exhaustive rather than random: every possible secret on Hard
+        let range = Difficulty::Hard.range();
+        for secret in range.clone() {
+            let secret = Guess::new(secret);
+            let mut solver = Solver::new(range.clone());
+            let mut guesses = 0;
+            loop {
+                let guess = solver.next_guess().unwrap();
+                guesses += 1;
+                let ordering = guess.cmp(&secret);
+                if ordering == Ordering::Equal {
+                    break;
+                }
+                solver.feedback(&guess, ordering);
+            }
+            assert!(guesses <= worst_case_bound(&range));
+        }
+    }
+
+    #[test]
+    fn contradictory_feedback_runs_out_of_guesses() {
+        let mut solver = Solver::new(1..=10);
+        solver.feedback(&Guess::new(5), Ordering::Less);
+        solver.feedback(&Guess::new(6), Ordering::Greater);
+        assert_eq!(None, solver.next_guess());
+    }
+
+    #[test]
+    fn auto_play_is_deterministic_and_within_bound() {
+        for difficulty in Difficulty::ALL {
+            let stats = auto_play(difficulty, 500, 42);
+            assert!(stats.within_bound(), "{:?}: {:?}", difficulty, stats);
+            assert!(stats.average >= 1.0 && stats.average <= stats.worst as f64);
+            assert_eq!(stats, auto_play(difficulty, 500, 42));
+        }
+    }
+}
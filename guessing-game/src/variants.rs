@@ -0,0 +1,366 @@
+// Every flavour of guessing game boils down to the same loop: read a
+// line, check it against a secret, tell the player how close they got.
+// The Variant trait captures that loop so the frontend doesn't care
+// which game it is running.
+
+use crate::{Difficulty, Game, Guess, Outcome, ParseGuessError, SecretSource};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Feedback {
+    Number(Outcome),
+    BullsAndCows {
+        bulls: usize,
+        cows: usize,
+        digits: usize,
+    },
+    Word(Vec<Mark>),
+}
+
+impl Feedback {
+    pub fn is_win(&self) -> bool {
+        match self {
+            Feedback::Number(outcome) => *outcome == Outcome::Win,
+            Feedback::BullsAndCows { bulls, digits, .. } => bulls == digits,
+            Feedback::Word(marks) => marks.iter().all(|mark| *mark == Mark::Correct),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    // Right letter, right place
+    Correct,
+    // Right letter, wrong place
+    Present,
+    Absent,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidGuess {
+    Number(ParseGuessError),
+    WrongLength { expected: usize },
+    NotDigits,
+    RepeatedDigits,
+    NotLetters,
+}
+
+impl fmt::Display for InvalidGuess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidGuess::Number(err) => write!(f, "{}", err),
+            InvalidGuess::WrongLength { expected } => {
+                write!(f, "your guess needs exactly {} characters", expected)
+            }
+            InvalidGuess::NotDigits => write!(f, "only the digits 0-9 are allowed"),
+            InvalidGuess::RepeatedDigits => write!(f, "every digit must be different"),
+            InvalidGuess::NotLetters => write!(f, "only the letters a-z are allowed"),
+        }
+    }
+}
+
+pub trait Variant {
+    // Err means the line wasn't a valid guess and no attempt was used
+    fn play(&mut self, input: &str) -> Result<Feedback, InvalidGuess>;
+    fn attempts(&self) -> u32;
+
+    // Hints need to see the classic game underneath
+    fn as_number_game(&self) -> Option<&Game> {
+        None
+    }
+}
+
+// The original game is just one variant among many
+impl Variant for Game {
+    fn play(&mut self, input: &str) -> Result<Feedback, InvalidGuess> {
+        let guess: Guess = input.parse().map_err(InvalidGuess::Number)?;
+        Ok(Feedback::Number(self.guess(&guess)))
+    }
+
+    fn attempts(&self) -> u32 {
+        Game::attempts(self)
+    }
+
+    fn as_number_game(&self) -> Option<&Game> {
+        Some(self)
+    }
+}
+
+// Guess a string of distinct digits. A "bull" is a digit in the right
+// position, a "cow" is a digit that's in the secret but somewhere else.
+pub struct BullsAndCows {
+    secret: Vec<u8>,
+    attempts: u32,
+}
+
+impl BullsAndCows {
+    pub fn new(source: &mut impl SecretSource, difficulty: Difficulty) -> BullsAndCows {
+        let digits = match difficulty {
+            Difficulty::Easy => 3,
+            Difficulty::Medium => 4,
+            Difficulty::Hard => 5,
+        };
+        let mut secret = Vec::with_capacity(digits);
+        while secret.len() < digits {
+            let digit = source.pick(0..=9) as u8;
+            if !secret.contains(&digit) {
+                secret.push(digit);
+            }
+        }
+        BullsAndCows {
+            secret,
+            attempts: 0,
+        }
+    }
+}
+
+impl Variant for BullsAndCows {
+    fn play(&mut self, input: &str) -> Result<Feedback, InvalidGuess> {
+        let input = input.trim();
+        let guess: Vec<u8> = input
+            .chars()
+            .map(|c| c.to_digit(10).map(|d| d as u8))
+            .collect::<Option<_>>()
+            .ok_or(InvalidGuess::NotDigits)?;
+        if guess.len() != self.secret.len() {
+            return Err(InvalidGuess::WrongLength {
+                expected: self.secret.len(),
+            });
+        }
+        if guess
+            .iter()
+            .enumerate()
+            .any(|(i, digit)| guess[..i].contains(digit))
+        {
+            return Err(InvalidGuess::RepeatedDigits);
+        }
+
+        self.attempts += 1;
+        let bulls = guess
+            .iter()
+            .zip(&self.secret)
+            .filter(|(guess, secret)| guess == secret)
+            .count();
+        let shared = guess
+            .iter()
+            .filter(|digit| self.secret.contains(digit))
+            .count();
+        Ok(Feedback::BullsAndCows {
+            bulls,
+            cows: shared - bulls,
+            digits: self.secret.len(),
+        })
+    }
+
+    fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+pub const WORDS: [&str; 24] = [
+    "apple", "beach", "brick", "cargo", "chain", "crate", "crust", "ferry", "flame", "ghost",
+    "grape", "house", "lemon", "macro", "mouse", "patch", "queue", "river", "scope", "stack",
+    "trait", "tuple", "vault", "yield",
+];
+
+// Guess a five letter word, with a mark for every letter
+pub struct WordGuess {
+    secret: Vec<char>,
+    attempts: u32,
+}
+
+impl WordGuess {
+    pub fn new(source: &mut impl SecretSource) -> WordGuess {
+        let index = source.pick(0..=WORDS.len() as i32 - 1) as usize;
+        WordGuess::with_word(WORDS[index])
+    }
+
+    pub fn with_word(word: &str) -> WordGuess {
+        WordGuess {
+            secret: word.chars().collect(),
+            attempts: 0,
+        }
+    }
+}
+
+impl Variant for WordGuess {
+    fn play(&mut self, input: &str) -> Result<Feedback, InvalidGuess> {
+        let guess: Vec<char> = input.trim().to_lowercase().chars().collect();
+        if !guess.iter().all(|c| c.is_ascii_lowercase()) {
+            return Err(InvalidGuess::NotLetters);
+        }
+        if guess.len() != self.secret.len() {
+            return Err(InvalidGuess::WrongLength {
+                expected: self.secret.len(),
+            });
+        }
+
+        self.attempts += 1;
+
+        // Exact matches first, then hand out "present" marks only for
+        // letters the secret still has left over. That way guessing
+        // "geese" against "crate" marks a single e, not three.
+        let mut marks = vec![Mark::Absent; guess.len()];
+        let mut leftover = Vec::new();
+        for (i, (g, s)) in guess.iter().zip(&self.secret).enumerate() {
+            if g == s {
+                marks[i] = Mark::Correct;
+            } else {
+                leftover.push(*s);
+            }
+        }
+        for (i, g) in guess.iter().enumerate() {
+            if marks[i] == Mark::Correct {
+                continue;
+            }
+            if let Some(pos) = leftover.iter().position(|s| s == g) {
+                leftover.swap_remove(pos);
+                marks[i] = Mark::Present;
+            }
+        }
+        Ok(Feedback::Word(marks))
+    }
+
+    fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VariantKind {
+    #[default]
+    Number,
+    BullsAndCows,
+    Word,
+}
+
+impl VariantKind {
+    pub fn start(
+        &self,
+        source: &mut impl SecretSource,
+        difficulty: Difficulty,
+    ) -> Box<dyn Variant> {
+        match self {
+            VariantKind::Number => Box::new(Game::with_difficulty(source, difficulty)),
+            VariantKind::BullsAndCows => Box::new(BullsAndCows::new(source, difficulty)),
+            VariantKind::Word => Box::new(WordGuess::new(source)),
+        }
+    }
+}
+
+impl fmt::Display for VariantKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            VariantKind::Number => "number",
+            VariantKind::BullsAndCows => "bulls-and-cows",
+            VariantKind::Word => "word",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for VariantKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<VariantKind, String> {
+        match s.trim().to_lowercase().as_str() {
+            "number" => Ok(VariantKind::Number),
+            "bulls-and-cows" | "mastermind" => Ok(VariantKind::BullsAndCows),
+            "word" => Ok(VariantKind::Word),
+            other => Err(format!("unknown variant '{}'", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::ops::RangeInclusive;
+
+    // Hands out the given values in order
+    struct Sequence(Vec<i32>);
+
+    impl SecretSource for Sequence {
+        fn pick(&mut self, _range: RangeInclusive<i32>) -> i32 {
+            self.0.remove(0)
+        }
+    }
+
+    #[test]
+    fn number_variant_wraps_the_original_game() {
+        let mut game = VariantKind::Number.start(&mut Sequence(vec![37]), Difficulty::Hard);
+        assert_eq!(Ok(Feedback::Number(Outcome::TooSmall)), game.play("20"));
+        assert_eq!(
+            Err(InvalidGuess::Number(ParseGuessError::NotANumber)),
+            game.play("1234x")
+        );
+        assert!(game.play("37").unwrap().is_win());
+        assert_eq!(2, game.attempts());
+    }
+
+    #[test]
+    fn bulls_and_cows_counts_positions() {
+        // Repeats from the source are skipped, so the secret is 1 2 3 4
+        let mut game = BullsAndCows::new(&mut Sequence(vec![1, 2, 2, 3, 4]), Difficulty::Medium);
+        let feedback = |bulls, cows| Feedback::BullsAndCows {
+            bulls,
+            cows,
+            digits: 4,
+        };
+        assert_eq!(Ok(feedback(0, 0)), game.play("5678"));
+        assert_eq!(Ok(feedback(2, 2)), game.play("1243"));
+        assert_eq!(Ok(feedback(0, 4)), game.play("4321"));
+        assert!(game.play("1234").unwrap().is_win());
+        assert_eq!(4, game.attempts());
+    }
+
+    #[test]
+    fn bulls_and_cows_rejects_bad_guesses() {
+        let mut game = BullsAndCows::new(&mut StdRng::seed_from_u64(1), Difficulty::Easy);
+        assert_eq!(
+            Err(InvalidGuess::WrongLength { expected: 3 }),
+            game.play("1234")
+        );
+        assert_eq!(Err(InvalidGuess::NotDigits), game.play("12a"));
+        assert_eq!(Err(InvalidGuess::RepeatedDigits), game.play("121"));
+        assert_eq!(0, game.attempts());
+    }
+
+    #[test]
+    fn word_marks_letters() {
+        use Mark::*;
+        let mut game = WordGuess::with_word("crate");
+        assert_eq!(
+            Ok(Feedback::Word(vec![
+                Absent, Absent, Absent, Absent, Correct
+            ])),
+            game.play("geese")
+        );
+        assert_eq!(
+            Ok(Feedback::Word(vec![
+                Present, Correct, Absent, Present, Absent
+            ])),
+            game.play("trick")
+        );
+        assert_eq!(
+            Err(InvalidGuess::WrongLength { expected: 5 }),
+            game.play("cat")
+        );
+        assert_eq!(Err(InvalidGuess::NotLetters), game.play("cr4te"));
+        assert!(game.play("CRATE").unwrap().is_win());
+        assert_eq!(3, game.attempts());
+    }
+
+    #[test]
+    fn variant_names_round_trip() {
+        for kind in [
+            VariantKind::Number,
+            VariantKind::BullsAndCows,
+            VariantKind::Word,
+        ] {
+            assert_eq!(Ok(kind), kind.to_string().parse());
+        }
+        assert!("chess".parse::<VariantKind>().is_err());
+    }
+}
@@ -1,17 +1,86 @@
 mod services;
 
+use chrono::NaiveDate;
+use std::{error::Error, time::Duration};
+
+pub use crate::services::back_of_house::kitchen;
 pub use crate::services::back_of_house::meals;
+pub use crate::services::back_of_house::menu;
+pub use crate::services::front_of_house::billing;
 pub use crate::services::front_of_house::hosting;
+pub use crate::services::front_of_house::hosting::reservations;
 pub use crate::services::front_of_house::serving;
 
-pub fn eat_at_restaurant() {
-    hosting::add_to_waitlist();
+// A quiet night: one 4-top, one party, straight to their table
+pub fn eat_at_restaurant() -> Result<Vec<hosting::Seating>, hosting::HostingError> {
+    let mut host = hosting::Host::new(vec![hosting::Table::new(1, 4, "main")], 60);
+    host.add_to_waitlist(hosting::Party::new("Ferris", 2))?;
+    Ok(host.seat_at_table(0))
+}
+
+// One table's order from start to finish: taken, cooked, served and
+// paid for. Hands back the receipt.
+pub fn serve_customer() -> Result<billing::Receipt, Box<dyn Error>> {
+    let mut order = serving::take_order(
+        1,
+        vec![
+            serving::LineItem::new("Soup", 1, 850),
+            serving::LineItem::new("Salad", 2, 1200),
+        ],
+    )?;
+    // Minutes take no time at all here, so nobody waits on a demo
+    let mut kitchen = kitchen::Kitchen::open(
+        vec![
+            kitchen::Recipe::new("Soup", kitchen::Station::Cold, 5),
+            kitchen::Recipe::new("Salad", kitchen::Station::Cold, 3),
+        ],
+        &[(kitchen::Station::Cold, 1)],
+        Duration::ZERO,
+    );
+    kitchen.cook(&mut order)?;
+    serving::serve_order(&mut order)?;
+
+    let rules = billing::BillingRules {
+        taxes: vec![billing::TaxRule::on_everything("GST", 1000)],
+        service_charge: 0,
+    };
+    let bill = billing::Bill::for_order(&order, &rules)?;
+    let payments = bill.split_evenly(&["Ferris"])?;
+    let mut receipts = billing::settle(&mut order, &bill, &payments)?;
+    Ok(receipts.remove(0))
 }
 
-pub fn serve_customer() {
-    serving::serve_order();
+// Wholemeal toast and whatever fruit the menu has in season on `date`.
+// None if the menu hasn't any.
+pub fn prepare_breakfast(menu: &menu::Menu, date: NaiveDate) -> Option<meals::Breakfast> {
+    meals::Breakfast::on(menu, date, "Wholemeal")
 }
 
-pub fn prepare_breakfast() -> meals::Breakfast {
-    meals::Breakfast::summer("Wholemeal")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn customers_get_a_table() {
+        let seated = eat_at_restaurant().unwrap();
+        assert_eq!(vec![1], seated[0].tables);
+    }
+
+    #[test]
+    fn customers_get_served_and_pay() {
+        let receipt = serve_customer().unwrap();
+        // $32.50 of food and 10% GST
+        assert_eq!(billing::Money::cents(3575), receipt.paid);
+        assert_eq!(receipt.total, receipt.paid);
+    }
+
+    #[test]
+    fn breakfast_comes_with_fruit_in_season() {
+        let menu = menu::Menu::load(concat!(env!("CARGO_MANIFEST_DIR"), "/menu.toml")).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let breakfast = prepare_breakfast(&menu, date).unwrap();
+        assert_eq!("Wholemeal", breakfast.toast);
+        assert_eq!("peaches", breakfast.seasonal_fruit());
+        assert_eq!(None, prepare_breakfast(&menu::Menu::default(), date));
+    }
 }
@@ -1 +1,3 @@
+pub mod kitchen;
 pub mod meals;
+pub mod menu;
@@ -0,0 +1,465 @@
+use crate::services::front_of_house::serving::{Order, OrderError};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// The kitchen: each station has its own queue of tickets and its own
+// cooks, each cook a thread. Tickets go in over a channel, any free cook
+// at the station takes the next one, and when it's done a Ready
+// notification comes back out over another channel.
+//
+// Prep times are in minutes, and how long a minute really lasts is up to
+// whoever opens the kitchen - tests don't want to wait a real hour.
+
+pub type TicketId = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Station {
+    Grill,
+    Fryer,
+    Cold,
+}
+
+impl fmt::Display for Station {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Station::Grill => "grill",
+            Station::Fryer => "fryer",
+            Station::Cold => "cold",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Where a dish gets made and how long it takes. A cook makes a whole
+// line of an order at once, however many were ordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    pub dish: String,
+    pub station: Station,
+    pub prep_minutes: u32,
+}
+
+impl Recipe {
+    pub fn new(dish: &str, station: Station, prep_minutes: u32) -> Recipe {
+        Recipe {
+            dish: String::from(dish),
+            station,
+            prep_minutes,
+        }
+    }
+}
+
+// One line of an order, on its way to a station
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub table: u32,
+    pub dish: String,
+    pub quantity: u32,
+    pub station: Station,
+    // How long until it should be ready, counting what's queued ahead
+    pub estimate_minutes: u32,
+}
+
+// "Order up!"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ready {
+    pub ticket: Ticket,
+    // Which of the station's cooks made it
+    pub cook: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KitchenError {
+    // Nobody knows how to make it
+    UnknownDish(String),
+    // There's a recipe, but nobody working the station it needs
+    NoCooks(Station),
+    Order(OrderError),
+}
+
+impl fmt::Display for KitchenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KitchenError::UnknownDish(dish) => write!(f, "the kitchen can't make {}", dish),
+            KitchenError::NoCooks(station) => write!(f, "nobody is working the {}", station),
+            KitchenError::Order(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for KitchenError {}
+
+impl From<OrderError> for KitchenError {
+    fn from(error: OrderError) -> KitchenError {
+        KitchenError::Order(error)
+    }
+}
+
+// One station's end of things
+struct Line {
+    // Each ticket with its prep time. None once the kitchen is closing,
+    // which tells the cooks to go home.
+    tickets: Option<Sender<(Ticket, u32)>>,
+    cooks: Vec<JoinHandle<()>>,
+    // When each cook will be done with everything they've been given so
+    // far. The queue is first come first served, so the next ticket goes
+    // to whichever of these comes soonest.
+    free_at: Vec<Instant>,
+}
+
+pub struct Kitchen {
+    recipes: Vec<Recipe>,
+    lines: HashMap<Station, Line>,
+    ready: Receiver<Ready>,
+    // Notifications that came in while we were waiting on something else
+    early: Vec<Ready>,
+    // Tickets sent that we haven't had a notification for yet
+    cooking: usize,
+    next_id: TicketId,
+    minute: Duration,
+    // Set at closing time. Cooks check it before starting on a ticket, so
+    // whatever's still queued is left uncooked.
+    closing: Arc<AtomicBool>,
+}
+
+impl Kitchen {
+    // `cooks` says how many work each station; `minute` is how long a
+    // minute of prep really takes
+    pub fn open(recipes: Vec<Recipe>, cooks: &[(Station, usize)], minute: Duration) -> Kitchen {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let closing = Arc::new(AtomicBool::new(false));
+        let mut lines = HashMap::new();
+        for &(station, count) in cooks.iter().filter(|(_, count)| *count > 0) {
+            let (tickets_tx, tickets_rx) = mpsc::channel::<(Ticket, u32)>();
+            // One queue, many cooks: whoever's free takes the next ticket
+            let queue = Arc::new(Mutex::new(tickets_rx));
+
+            let cooks = (0..count)
+                .map(|cook| {
+                    let queue = Arc::clone(&queue);
+                    let ready = ready_tx.clone();
+                    let closing = Arc::clone(&closing);
+                    thread::spawn(move || loop {
+                        // The lock is only held while taking a ticket, not
+                        // while cooking it
+                        let ticket = queue.lock().unwrap().recv();
+                        let Ok((ticket, prep)) = ticket else {
+                            // The kitchen's closed
+                            break;
+                        };
+                        // Tickets are still handed out after the queue's
+                        // hung up, until it's empty; they can wait
+                        if closing.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        thread::sleep(minute * prep);
+                        // Nobody listening means the kitchen is closing;
+                        // the food is done either way
+                        let _ = ready.send(Ready { ticket, cook });
+                    })
+                })
+                .collect();
+
+            lines.insert(
+                station,
+                Line {
+                    tickets: Some(tickets_tx),
+                    cooks,
+                    free_at: vec![Instant::now(); count],
+                },
+            );
+        }
+
+        Kitchen {
+            recipes,
+            lines,
+            ready: ready_rx,
+            early: Vec::new(),
+            cooking: 0,
+            next_id: 1,
+            minute,
+            closing,
+        }
+    }
+
+    // Minutes until a new ticket for `station` would be picked up, which
+    // is as soon as the first of its cooks is free
+    pub fn queue_minutes(&self, station: Station) -> Option<u32> {
+        let line = self.lines.get(&station)?;
+        let soonest = line.free_at.iter().min().expect("every line has a cook");
+        Some(self.minutes_until(*soonest))
+    }
+
+    // Rounded up, so a ticket that's nearly done still counts a minute
+    fn minutes_until(&self, at: Instant) -> u32 {
+        let left = at.saturating_duration_since(Instant::now()).as_nanos();
+        match self.minute.as_nanos() {
+            0 => 0,
+            minute => left.div_ceil(minute) as u32,
+        }
+    }
+
+    // Starts cooking the order: one ticket per line, each sent to its
+    // station. Nothing is sent unless the kitchen can make all of it.
+    pub fn send(&mut self, order: &mut Order) -> Result<Vec<Ticket>, KitchenError> {
+        let mut recipes = Vec::new();
+        for item in order.items() {
+            let recipe = self
+                .recipe(&item.dish)
+                .ok_or_else(|| KitchenError::UnknownDish(item.dish.clone()))?;
+            if !self.lines.contains_key(&recipe.station) {
+                return Err(KitchenError::NoCooks(recipe.station));
+            }
+            recipes.push(recipe.clone());
+        }
+        order.start_cooking()?;
+
+        let mut tickets = Vec::new();
+        for (item, recipe) in order.items().iter().zip(recipes) {
+            let line = self.lines.get_mut(&recipe.station).expect("checked above");
+            let free_at = line
+                .free_at
+                .iter_mut()
+                .min()
+                .expect("every line has a cook");
+            *free_at = (*free_at).max(Instant::now()) + self.minute * recipe.prep_minutes;
+            let done_at = *free_at;
+            let estimate = self.minutes_until(done_at);
+            let ticket = Ticket {
+                id: self.next_id,
+                table: order.table,
+                dish: item.dish.clone(),
+                quantity: item.quantity,
+                station: recipe.station,
+                estimate_minutes: estimate,
+            };
+            self.next_id += 1;
+
+            let line = &self.lines[&recipe.station];
+            line.tickets
+                .as_ref()
+                .expect("only closed when dropped")
+                .send((ticket.clone(), recipe.prep_minutes))
+                .expect("cooks only go home when the kitchen closes");
+            self.cooking += 1;
+            tickets.push(ticket);
+        }
+        Ok(tickets)
+    }
+
+    // The next thing to come out of the kitchen, blocking until something
+    // does. None if nothing's cooking.
+    pub fn next_ready(&mut self) -> Option<Ready> {
+        if !self.early.is_empty() {
+            return Some(self.early.remove(0));
+        }
+        self.receive()
+    }
+
+    // Blocks until every one of `tickets` is ready. Anything else that
+    // comes out meanwhile is kept for next_ready().
+    pub fn wait_for(&mut self, tickets: &[Ticket]) -> Vec<Ready> {
+        let mut done = Vec::new();
+        self.early.retain(|ready| {
+            let ours = tickets.iter().any(|t| t.id == ready.ticket.id);
+            if ours {
+                done.push(ready.clone());
+            }
+            !ours
+        });
+        while done.len() < tickets.len() {
+            let Some(ready) = self.receive() else {
+                // Not tickets of ours, or already handed out
+                break;
+            };
+            if tickets.iter().any(|t| t.id == ready.ticket.id) {
+                done.push(ready);
+            } else {
+                self.early.push(ready);
+            }
+        }
+        done
+    }
+
+    // The whole order, start to finish: send it, wait for every ticket
+    // and mark the order ready to serve
+    pub fn cook(&mut self, order: &mut Order) -> Result<Vec<Ready>, KitchenError> {
+        let tickets = self.send(order)?;
+        let done = self.wait_for(&tickets);
+        order.finish_cooking()?;
+        Ok(done)
+    }
+
+    fn recipe(&self, dish: &str) -> Option<&Recipe> {
+        self.recipes
+            .iter()
+            .find(|recipe| recipe.dish.eq_ignore_ascii_case(dish))
+    }
+
+    fn receive(&mut self) -> Option<Ready> {
+        if self.cooking == 0 {
+            return None;
+        }
+        let ready = self.ready.recv().expect("cooks finish what they start");
+        self.cooking -= 1;
+        Some(ready)
+    }
+}
+
+// Closing time: the cooks finish the dish they're on, leave any tickets
+// still queued and go home, and we wait for them. Hanging up the queues
+// wakes the cooks who were waiting for a ticket.
+impl Drop for Kitchen {
+    fn drop(&mut self) {
+        self.closing.store(true, Ordering::SeqCst);
+        for line in self.lines.values_mut() {
+            drop(line.tickets.take());
+        }
+        for line in self.lines.values_mut() {
+            for cook in line.cooks.drain(..) {
+                // A cook that panicked has gone home already; panicking
+                // about it here, maybe mid-unwind, would abort
+                let _ = cook.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::front_of_house::serving::{take_order, LineItem, OrderStatus};
+
+    fn recipes() -> Vec<Recipe> {
+        vec![
+            Recipe::new("Steak", Station::Grill, 10),
+            Recipe::new("Burger", Station::Grill, 8),
+            Recipe::new("Fries", Station::Fryer, 5),
+            Recipe::new("Salad", Station::Cold, 2),
+        ]
+    }
+
+    fn order(dishes: &[&str]) -> Order {
+        let items = dishes
+            .iter()
+            .map(|dish| LineItem::new(dish, 1, 1000))
+            .collect();
+        take_order(7, items).unwrap()
+    }
+
+    #[test]
+    fn cooks_a_whole_order() {
+        let mut kitchen = Kitchen::open(
+            recipes(),
+            &[(Station::Grill, 1), (Station::Fryer, 1), (Station::Cold, 1)],
+            Duration::from_millis(1),
+        );
+        let mut order = order(&["Steak", "Fries", "Salad"]);
+        let done = kitchen.cook(&mut order).unwrap();
+        assert_eq!(OrderStatus::Ready, order.status());
+        let mut dishes: Vec<&str> = done.iter().map(|r| r.ticket.dish.as_str()).collect();
+        dishes.sort();
+        assert_eq!(vec!["Fries", "Salad", "Steak"], dishes);
+        assert!(done.iter().all(|r| r.ticket.table == 7));
+        assert_eq!(None, kitchen.next_ready());
+    }
+
+    #[test]
+    fn estimates_count_the_queue() {
+        // Slow minutes, so nothing finishes while we're still sending
+        let mut kitchen = Kitchen::open(
+            recipes(),
+            &[(Station::Grill, 1), (Station::Fryer, 2)],
+            Duration::from_millis(10),
+        );
+        let tickets = kitchen
+            .send(&mut order(&["Steak", "Burger", "Fries", "Fries"]))
+            .unwrap();
+        let estimates: Vec<u32> = tickets.iter().map(|t| t.estimate_minutes).collect();
+        // One cook on the grill does them one after the other; two on
+        // the fryer fry at the same time
+        assert_eq!(vec![10, 18, 5, 5], estimates);
+        assert_eq!(Some(18), kitchen.queue_minutes(Station::Grill));
+        assert_eq!(Some(5), kitchen.queue_minutes(Station::Fryer));
+        assert_eq!(None, kitchen.queue_minutes(Station::Cold));
+        kitchen.wait_for(&tickets);
+        assert_eq!(Some(0), kitchen.queue_minutes(Station::Grill));
+    }
+
+    #[test]
+    fn several_cooks_work_at_once() {
+        let mut kitchen =
+            Kitchen::open(recipes(), &[(Station::Grill, 2)], Duration::from_millis(10));
+        let started = Instant::now();
+        let done = kitchen.cook(&mut order(&["Steak", "Steak"])).unwrap();
+        // Each steak takes 100ms; one after the other would be 200ms
+        assert!(started.elapsed() < Duration::from_millis(190));
+        assert_ne!(done[0].cook, done[1].cook);
+    }
+
+    #[test]
+    fn notifications_for_other_orders_wait_their_turn() {
+        let mut kitchen = Kitchen::open(
+            recipes(),
+            &[(Station::Grill, 1), (Station::Cold, 1)],
+            Duration::from_millis(5),
+        );
+        let salad = kitchen.send(&mut order(&["Salad"])).unwrap();
+        let steak = kitchen.send(&mut order(&["Steak"])).unwrap();
+        // The salad's done long before the steak, but we asked for the steak
+        let done = kitchen.wait_for(&steak);
+        assert_eq!("Steak", done[0].ticket.dish);
+        assert_eq!(salad[0], kitchen.next_ready().unwrap().ticket);
+        assert_eq!(None, kitchen.next_ready());
+    }
+
+    #[test]
+    fn closing_leaves_the_queue_uncooked() {
+        let mut kitchen =
+            Kitchen::open(recipes(), &[(Station::Grill, 1)], Duration::from_millis(10));
+        // A second of steaks, one after the other
+        kitchen.send(&mut order(&["Steak"; 10])).unwrap();
+        let started = Instant::now();
+        drop(kitchen);
+        // Only the steak already on the grill gets finished
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn refuses_what_it_cannot_make() {
+        let mut kitchen = Kitchen::open(recipes(), &[(Station::Grill, 1)], Duration::ZERO);
+        let mut order = order(&["Steak", "Soufflé"]);
+        assert_eq!(
+            Err(KitchenError::UnknownDish(String::from("Soufflé"))),
+            kitchen.send(&mut order)
+        );
+        let mut salad = self::order(&["Salad"]);
+        assert_eq!(
+            Err(KitchenError::NoCooks(Station::Cold)),
+            kitchen.send(&mut salad)
+        );
+        // Neither order got anywhere near the kitchen
+        assert_eq!(OrderStatus::Placed, order.status());
+        assert_eq!(OrderStatus::Placed, salad.status());
+
+        let mut steak = self::order(&["Steak"]);
+        kitchen.cook(&mut steak).unwrap();
+        assert_eq!(
+            Err(KitchenError::Order(OrderError::IllegalTransition {
+                from: OrderStatus::Ready,
+                to: OrderStatus::Cooking
+            })),
+            kitchen.send(&mut steak)
+        );
+    }
+}
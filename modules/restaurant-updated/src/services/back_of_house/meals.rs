@@ -1,18 +1,116 @@
+use super::menu::{Category, Menu, MenuItem};
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Appetizer {
     Soup(String),
     Salad,
 }
 
+impl Appetizer {
+    // What the menu calls it: soups go by "<kind> soup"
+    pub fn name(&self) -> String {
+        match self {
+            Appetizer::Soup(kind) => format!("{} soup", kind),
+            Appetizer::Salad => String::from("Salad"),
+        }
+    }
+
+    // The other way round. Only soups and salad are appetizers we know
+    // how to make. Case doesn't matter, same as for Menu::find.
+    pub fn from_item(item: &MenuItem) -> Option<Appetizer> {
+        const SOUP: &str = " soup";
+        if item.category != Category::Appetizer {
+            return None;
+        }
+        if item.name.eq_ignore_ascii_case("salad") {
+            return Some(Appetizer::Salad);
+        }
+        let split = item.name.len().checked_sub(SOUP.len())?;
+        let (kind, suffix) = (item.name.get(..split)?, item.name.get(split..)?);
+        if !suffix.eq_ignore_ascii_case(SOUP) {
+            return None;
+        }
+        Some(Appetizer::Soup(String::from(kind)))
+    }
+
+    pub fn on_menu<'a>(&self, menu: &'a Menu) -> Option<&'a MenuItem> {
+        menu.find(&self.name())
+            .filter(|item| item.category == Category::Appetizer)
+    }
+}
+
+// The appetizers being served on `date`
+pub fn appetizers(menu: &Menu, date: NaiveDate) -> Vec<Appetizer> {
+    menu.in_category(Category::Appetizer)
+        .filter(|item| item.available_on(date))
+        .filter_map(Appetizer::from_item)
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Breakfast {
     pub toast: String,
-    _seasonal_fruit: String,
+    // Customers pick the toast, the kitchen picks the fruit
+    seasonal_fruit: String,
 }
 
 impl Breakfast {
-    pub fn summer(toast: &str) -> Breakfast {
-        Breakfast {
+    // Breakfast with whichever fruit the menu has in season on `date`,
+    // if it has any
+    pub fn on(menu: &Menu, date: NaiveDate, toast: &str) -> Option<Breakfast> {
+        let fruit = menu.seasonal_fruit(date)?;
+        Some(Breakfast {
             toast: String::from(toast),
-            _seasonal_fruit: String::from("peaches"),
-        }
+            seasonal_fruit: fruit.name.to_lowercase(),
+        })
+    }
+
+    pub fn seasonal_fruit(&self) -> &str {
+        &self.seasonal_fruit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> Menu {
+        Menu::load(concat!(env!("CARGO_MANIFEST_DIR"), "/menu.toml")).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn breakfast_fruit_depends_on_the_date() {
+        let menu = menu();
+        let summer = Breakfast::on(&menu, date(8, 1), "Rye").unwrap();
+        assert_eq!("Rye", summer.toast);
+        assert_eq!("peaches", summer.seasonal_fruit());
+        let winter = Breakfast::on(&menu, date(12, 25), "Sourdough").unwrap();
+        assert_eq!("oranges", winter.seasonal_fruit());
+        assert_eq!(None, Breakfast::on(&Menu::default(), date(8, 1), "Rye"));
+    }
+
+    #[test]
+    fn appetizers_are_menu_items() {
+        let menu = menu();
+        let soup = Appetizer::Soup(String::from("Tomato"));
+        assert_eq!(850, soup.on_menu(&menu).unwrap().price);
+        assert_eq!(None, Appetizer::Soup(String::from("Pea")).on_menu(&menu));
+        assert_eq!(vec![soup, Appetizer::Salad], appetizers(&menu, date(1, 1)));
+        assert_eq!(vec![Appetizer::Salad], appetizers(&menu, date(7, 1)));
+        assert_eq!(None, Appetizer::from_item(menu.find("Steak").unwrap()));
+
+        let mut shouting = menu.find("Tomato soup").unwrap().clone();
+        shouting.name = String::from("Pea SOUP");
+        assert_eq!(
+            Some(Appetizer::Soup(String::from("Pea"))),
+            Appetizer::from_item(&shouting)
+        );
+        shouting.name = String::from("Soup");
+        assert_eq!(None, Appetizer::from_item(&shouting));
     }
 }
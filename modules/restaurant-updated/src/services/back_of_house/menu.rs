@@ -0,0 +1,335 @@
+use crate::services::front_of_house::serving::LineItem;
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::{fmt, fs, io, path::Path};
+
+// Everything the kitchen can make, read from a TOML file like this:
+//
+//     [[item]]
+//     name = "Tomato soup"
+//     category = "appetizer"
+//     price = 850                      # in cents
+//     allergens = ["celery"]
+//     tags = ["vegetarian", "vegan"]
+//     seasons = ["autumn", "winter"]   # leave out for all year round
+//
+// See menu.toml at the root of the crate for a whole one.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    Appetizer,
+    Breakfast,
+    Main,
+    Dessert,
+    Drink,
+    // Comes with breakfast, whichever is in season
+    Fruit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    // Going by the month, northern hemisphere style
+    pub fn of(date: NaiveDate) -> Season {
+        match date.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Egg,
+    Peanuts,
+    TreeNuts,
+    Soy,
+    Fish,
+    Shellfish,
+    Sesame,
+    Celery,
+    Mustard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Diet {
+    Vegetarian,
+    Vegan,
+    GlutenFree,
+    Halal,
+}
+
+// Misspelled keys are an error rather than being skipped: an item whose
+// "alergens" went missing would be offered to people with the allergy
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuItem {
+    pub name: String,
+    pub category: Category,
+    // In cents
+    pub price: u64,
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+    #[serde(default)]
+    pub tags: Vec<Diet>,
+    // Empty means all year round
+    #[serde(default)]
+    pub seasons: Vec<Season>,
+}
+
+impl MenuItem {
+    pub fn available_on(&self, date: NaiveDate) -> bool {
+        self.seasons.is_empty() || self.seasons.contains(&Season::of(date))
+    }
+
+    pub fn is(&self, diet: Diet) -> bool {
+        self.tags.contains(&diet)
+    }
+
+    pub fn contains(&self, allergen: Allergen) -> bool {
+        self.allergens.contains(&allergen)
+    }
+
+    // So many of this, ready to go on an order
+    pub fn line_item(&self, quantity: u32) -> LineItem {
+        LineItem::new(&self.name, quantity, self.price)
+    }
+}
+
+#[derive(Debug)]
+pub enum MenuError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    // Two items with the same name; we'd never know which one was ordered
+    Duplicate(String),
+}
+
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MenuError::Io(error) => write!(f, "couldn't read the menu: {}", error),
+            MenuError::Toml(error) => write!(f, "couldn't understand the menu: {}", error),
+            MenuError::Duplicate(name) => write!(f, "{} is on the menu twice", name),
+        }
+    }
+}
+
+impl std::error::Error for MenuError {}
+
+impl From<io::Error> for MenuError {
+    fn from(error: io::Error) -> MenuError {
+        MenuError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for MenuError {
+    fn from(error: toml::de::Error) -> MenuError {
+        MenuError::Toml(error)
+    }
+}
+
+// What the TOML file holds: a list of [[item]] tables
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MenuFile {
+    #[serde(default, rename = "item")]
+    items: Vec<MenuItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Menu {
+    items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn from_toml(text: &str) -> Result<Menu, MenuError> {
+        let file: MenuFile = toml::from_str(text)?;
+        for (i, item) in file.items.iter().enumerate() {
+            if file.items[..i]
+                .iter()
+                .any(|other| same_name(&other.name, &item.name))
+            {
+                return Err(MenuError::Duplicate(item.name.clone()));
+            }
+        }
+        Ok(Menu { items: file.items })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Menu, MenuError> {
+        Menu::from_toml(&fs::read_to_string(path)?)
+    }
+
+    // In the order the file lists them
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    // Names are matched ignoring case, so "tomato soup" finds "Tomato soup"
+    pub fn find(&self, name: &str) -> Option<&MenuItem> {
+        self.items.iter().find(|item| same_name(&item.name, name))
+    }
+
+    pub fn in_category(&self, category: Category) -> impl Iterator<Item = &MenuItem> {
+        self.items
+            .iter()
+            .filter(move |item| item.category == category)
+    }
+
+    pub fn available_on(&self, date: NaiveDate) -> impl Iterator<Item = &MenuItem> {
+        self.items
+            .iter()
+            .filter(move |item| item.available_on(date))
+    }
+
+    // What someone who can't eat any of `allergens` and only eats `diet`
+    // (if they have one) can order on `date`
+    pub fn suitable(
+        &self,
+        date: NaiveDate,
+        diet: Option<Diet>,
+        allergens: &[Allergen],
+    ) -> Vec<&MenuItem> {
+        self.available_on(date)
+            .filter(|item| diet.is_none_or(|diet| item.is(diet)))
+            .filter(|item| !allergens.iter().any(|a| item.contains(*a)))
+            .collect()
+    }
+
+    // The fruit that comes with breakfast on `date`: the first one the
+    // menu lists that's in season
+    pub fn seasonal_fruit(&self, date: NaiveDate) -> Option<&MenuItem> {
+        self.in_category(Category::Fruit)
+            .find(|item| item.available_on(date))
+    }
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn menu() -> Menu {
+        Menu::load(concat!(env!("CARGO_MANIFEST_DIR"), "/menu.toml")).unwrap()
+    }
+
+    #[test]
+    fn loads_the_menu_file() {
+        let menu = menu();
+        let soup = menu.find("tomato SOUP").unwrap();
+        assert_eq!(Category::Appetizer, soup.category);
+        assert_eq!(850, soup.price);
+        assert!(soup.is(Diet::Vegan));
+        assert!(soup.contains(Allergen::Celery));
+        assert_eq!(2, menu.in_category(Category::Appetizer).count());
+        assert_eq!(
+            Some(1275),
+            menu.find("Pancakes").unwrap().line_item(3).total()
+        );
+    }
+
+    #[test]
+    fn seasons_follow_the_date() {
+        assert_eq!(Season::Summer, Season::of(date(7, 14)));
+        assert_eq!(Season::Winter, Season::of(date(1, 1)));
+        assert_eq!(Season::Winter, Season::of(date(12, 31)));
+
+        let menu = menu();
+        let fruit = |month| menu.seasonal_fruit(date(month, 1)).unwrap().name.as_str();
+        assert_eq!("Peaches", fruit(7));
+        assert_eq!("Apples", fruit(10));
+        assert_eq!("Oranges", fruit(1));
+        assert_eq!("Strawberries", fruit(4));
+        // Soup is off in summer, the salad's on all year
+        let summer: Vec<&str> = menu
+            .available_on(date(7, 1))
+            .filter(|item| item.category == Category::Appetizer)
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(vec!["Salad"], summer);
+    }
+
+    #[test]
+    fn filters_by_diet_and_allergens() {
+        let menu = menu();
+        let names = |items: Vec<&MenuItem>| -> Vec<String> {
+            items
+                .iter()
+                .filter(|item| item.category != Category::Fruit)
+                .map(|item| item.name.clone())
+                .collect()
+        };
+        assert_eq!(
+            vec!["Tomato soup", "Salad", "Lemonade"],
+            names(menu.suitable(date(1, 1), Some(Diet::Vegan), &[]))
+        );
+        assert_eq!(
+            vec!["Salad", "Steak", "Lemonade"],
+            names(menu.suitable(date(1, 1), None, &[Allergen::Gluten, Allergen::Celery]))
+        );
+    }
+
+    #[test]
+    fn bad_menus_are_rejected() {
+        let twice = r#"
+            [[item]]
+            name = "Salad"
+            category = "appetizer"
+            price = 900
+
+            [[item]]
+            name = "salad"
+            category = "main"
+            price = 1400
+        "#;
+        assert!(
+            matches!(Menu::from_toml(twice), Err(MenuError::Duplicate(name)) if name == "salad")
+        );
+
+        let unknown = r#"
+            [[item]]
+            name = "Mystery"
+            category = "snack"
+            price = 100
+        "#;
+        assert!(matches!(Menu::from_toml(unknown), Err(MenuError::Toml(_))));
+
+        let typo = r#"
+            [[item]]
+            name = "Satay"
+            category = "main"
+            price = 1600
+            alergens = ["peanuts"]
+        "#;
+        assert!(matches!(Menu::from_toml(typo), Err(MenuError::Toml(_))));
+        assert!(matches!(
+            Menu::from_toml("[[items]]\nname = \"Satay\""),
+            Err(MenuError::Toml(_))
+        ));
+        assert!(matches!(
+            Menu::load("no/such/menu.toml"),
+            Err(MenuError::Io(_))
+        ));
+        assert_eq!(Menu::default(), Menu::from_toml("").unwrap());
+    }
+}
@@ -0,0 +1,850 @@
+use super::serving::{self, LineItem, Order, OrderError};
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Neg, Sub},
+};
+
+// Turning an order into a bill and the bill into payments.
+//
+// All money is kept in cents. Percentages are kept in basis points
+// (hundredths of a percent, so 10% is 1000) so that working out 12.5%
+// of something never involves a float. Whenever an amount has to be
+// shared out - a discount across the lines, a bill between friends -
+// it's shared so the parts add back up to the exact whole.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Money(i64);
+
+impl Money {
+    pub fn cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    pub fn as_cents(self) -> i64 {
+        self.0
+    }
+
+    // This much of it, in basis points, rounding half a cent up
+    pub fn percent(self, basis_points: u32) -> Money {
+        Money(round_div(self.0 * i64::from(basis_points), 10_000))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.abs();
+        let text = format!("{}${}.{:02}", sign, cents / 100, cents % 100);
+        // Let callers line up columns with {:>10}
+        f.pad(&text)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money(0), Add::add)
+    }
+}
+
+// A tax on some dishes, or on everything if `dishes` is empty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaxRule {
+    pub name: String,
+    pub rate: u32,
+    pub dishes: Vec<String>,
+}
+
+impl TaxRule {
+    pub fn on_everything(name: &str, rate: u32) -> TaxRule {
+        TaxRule {
+            name: String::from(name),
+            rate,
+            dishes: Vec::new(),
+        }
+    }
+
+    pub fn on_dishes(name: &str, rate: u32, dishes: &[&str]) -> TaxRule {
+        TaxRule {
+            name: String::from(name),
+            rate,
+            dishes: dishes.iter().map(|dish| dish.to_string()).collect(),
+        }
+    }
+
+    fn applies_to(&self, dish: &str) -> bool {
+        self.dishes.is_empty() || self.dishes.iter().any(|d| d.eq_ignore_ascii_case(dish))
+    }
+}
+
+// What the house charges on top of the food
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BillingRules {
+    pub taxes: Vec<TaxRule>,
+    // On the food after discounts; it isn't taxed itself
+    pub service_charge: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discount {
+    // Off the subtotal, in basis points
+    Percent(u32),
+    Amount(Money),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coupon {
+    pub code: String,
+    pub discount: Discount,
+    // The subtotal has to be at least this for the coupon to work
+    pub min_spend: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BillingError {
+    BelowMinimumSpend { code: String, min_spend: Money },
+    CouponAlreadyUsed(String),
+    // An amount off that would put money on the bill instead
+    NegativeDiscount(String),
+    NoPayers,
+    // Splitting by item: a line nobody's paying for, or one that isn't
+    // on the bill at all
+    UnclaimedLine(usize),
+    NoSuchLine(usize),
+    // The payments don't add up to the bill
+    WrongAmount { expected: Money, paid: Money },
+    // A line (or the whole order) too big to put a price on
+    TooExpensive(String),
+    // Paying one order with another order's bill
+    NotThisOrder { bill_table: u32, order_table: u32 },
+    Order(OrderError),
+}
+
+impl fmt::Display for BillingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BillingError::BelowMinimumSpend { code, min_spend } => {
+                write!(f, "{} needs a spend of at least {}", code, min_spend)
+            }
+            BillingError::CouponAlreadyUsed(code) => write!(f, "{} has already been used", code),
+            BillingError::NegativeDiscount(label) => {
+                write!(f, "{} can't take a negative amount off", label)
+            }
+            BillingError::NoPayers => write!(f, "somebody has to pay"),
+            BillingError::UnclaimedLine(line) => write!(f, "nobody is paying for line {}", line),
+            BillingError::NoSuchLine(line) => write!(f, "there's no line {} on the bill", line),
+            BillingError::WrongAmount { expected, paid } => {
+                write!(f, "the bill is {} but {} was paid", expected, paid)
+            }
+            BillingError::TooExpensive(dish) => write!(f, "{} costs too much to bill", dish),
+            BillingError::NotThisOrder {
+                bill_table,
+                order_table,
+            } => write!(
+                f,
+                "the bill for table {} doesn't match the order for table {}",
+                bill_table, order_table
+            ),
+            BillingError::Order(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BillingError {}
+
+impl From<OrderError> for BillingError {
+    fn from(error: OrderError) -> BillingError {
+        BillingError::Order(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillLine {
+    pub dish: String,
+    pub quantity: u32,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bill {
+    pub table: u32,
+    lines: Vec<BillLine>,
+    rules: BillingRules,
+    // What each discount is called on the receipt, and what it is
+    discounts: Vec<(String, Discount)>,
+}
+
+impl Bill {
+    pub fn for_order(order: &Order, rules: &BillingRules) -> Result<Bill, BillingError> {
+        let lines = order
+            .items()
+            .iter()
+            .map(|item| {
+                Ok(BillLine {
+                    dish: item.dish.clone(),
+                    quantity: item.quantity,
+                    amount: line_amount(item)?,
+                })
+            })
+            .collect::<Result<Vec<_>, BillingError>>()?;
+        // The lines might each fit and still not add up
+        if order
+            .total()
+            .and_then(|total| i64::try_from(total).ok())
+            .is_none()
+        {
+            return Err(BillingError::TooExpensive(format!("table {}", order.table)));
+        }
+        Ok(Bill {
+            table: order.table,
+            lines,
+            rules: rules.clone(),
+            discounts: Vec::new(),
+        })
+    }
+
+    pub fn lines(&self) -> &[BillLine] {
+        &self.lines
+    }
+
+    // Whether this is the bill for `order`: same table, same lines
+    pub fn is_for(&self, order: &Order) -> bool {
+        self.table == order.table
+            && self.lines.len() == order.items().len()
+            && self.lines.iter().zip(order.items()).all(|(line, item)| {
+                line.dish == item.dish
+                    && line.quantity == item.quantity
+                    && line_amount(item).is_ok_and(|amount| amount == line.amount)
+            })
+    }
+
+    // The house's own discounts: happy hour, the staff meal...
+    pub fn discount(&mut self, label: &str, discount: Discount) -> Result<(), BillingError> {
+        check_discount(label, discount)?;
+        self.discounts.push((String::from(label), discount));
+        Ok(())
+    }
+
+    pub fn apply_coupon(&mut self, coupon: &Coupon) -> Result<(), BillingError> {
+        let label = format!("Coupon {}", coupon.code);
+        check_discount(&coupon.code, coupon.discount)?;
+        if self.discounts.iter().any(|(l, _)| *l == label) {
+            return Err(BillingError::CouponAlreadyUsed(coupon.code.clone()));
+        }
+        if self.subtotal() < coupon.min_spend {
+            return Err(BillingError::BelowMinimumSpend {
+                code: coupon.code.clone(),
+                min_spend: coupon.min_spend,
+            });
+        }
+        self.discounts.push((label, coupon.discount));
+        Ok(())
+    }
+
+    // The food, before anything comes off or goes on
+    pub fn subtotal(&self) -> Money {
+        self.lines.iter().map(|line| line.amount).sum()
+    }
+
+    // Each discount's amount off, in the order they were given. Every
+    // percentage is of the subtotal, and nothing takes the bill below
+    // zero.
+    pub fn discounts(&self) -> Vec<(String, Money)> {
+        let mut left = self.subtotal();
+        self.discounts
+            .iter()
+            .map(|(label, discount)| {
+                let off = match discount {
+                    Discount::Percent(rate) => self.subtotal().percent(*rate),
+                    Discount::Amount(amount) => *amount,
+                }
+                .min(left);
+                left = left - off;
+                (label.clone(), off)
+            })
+            .collect()
+    }
+
+    // Each line after its share of the discounts
+    fn net_lines(&self) -> Vec<Money> {
+        let off: Money = self.discounts().iter().map(|(_, off)| *off).sum();
+        let weights: Vec<i64> = self.lines.iter().map(|l| l.amount.as_cents()).collect();
+        allocate(off.as_cents(), &weights)
+            .into_iter()
+            .zip(&self.lines)
+            .map(|(share, line)| line.amount - Money::cents(share))
+            .collect()
+    }
+
+    // What each line costs once everything's on: its share of the
+    // discounts, of the service charge, and of every tax that applies to
+    // it. The costs add up to exactly the total.
+    fn line_costs(&self) -> Vec<i64> {
+        let net: Vec<i64> = self.net_lines().iter().map(|m| m.as_cents()).collect();
+        let mut costs = net.clone();
+        let service = allocate(self.service_charge().as_cents(), &net);
+        for (rule, (_, tax)) in self.rules.taxes.iter().zip(self.taxes()) {
+            // Lines the tax doesn't apply to don't get any of it
+            let taxed: Vec<i64> = self
+                .lines
+                .iter()
+                .zip(&net)
+                .map(|(line, &amount)| {
+                    if rule.applies_to(&line.dish) {
+                        amount
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            for (cost, share) in costs.iter_mut().zip(allocate(tax.as_cents(), &taxed)) {
+                *cost += share;
+            }
+        }
+        for (cost, share) in costs.iter_mut().zip(service) {
+            *cost += share;
+        }
+        costs
+    }
+
+    pub fn service_charge(&self) -> Money {
+        let net: Money = self.net_lines().into_iter().sum();
+        net.percent(self.rules.service_charge)
+    }
+
+    // Each tax on whatever it applies to, after discounts
+    pub fn taxes(&self) -> Vec<(String, Money)> {
+        let net = self.net_lines();
+        self.rules
+            .taxes
+            .iter()
+            .map(|rule| {
+                let taxed: Money = self
+                    .lines
+                    .iter()
+                    .zip(&net)
+                    .filter(|(line, _)| rule.applies_to(&line.dish))
+                    .map(|(_, amount)| *amount)
+                    .sum();
+                (rule.name.clone(), taxed.percent(rule.rate))
+            })
+            .collect()
+    }
+
+    pub fn total(&self) -> Money {
+        let net: Money = self.net_lines().into_iter().sum();
+        let taxes: Money = self.taxes().iter().map(|(_, tax)| *tax).sum();
+        net + self.service_charge() + taxes
+    }
+
+    // Everyone pays the same, give or take a cent; the first payers
+    // pick up any odd cents
+    pub fn split_evenly(&self, payers: &[&str]) -> Result<Vec<Payment>, BillingError> {
+        if payers.is_empty() {
+            return Err(BillingError::NoPayers);
+        }
+        let shares = allocate(self.total().as_cents(), &vec![1; payers.len()]);
+        Ok(payments(payers, shares))
+    }
+
+    // Everyone pays for what they had. `claims` says which lines (by
+    // index) each payer had; a line claimed by several payers is shared
+    // between them. Each line brings its own discount, service and
+    // taxes along, so nobody pays a tax on a dish they didn't have.
+    pub fn split_by_item(&self, claims: &[(&str, &[usize])]) -> Result<Vec<Payment>, BillingError> {
+        if claims.is_empty() {
+            return Err(BillingError::NoPayers);
+        }
+        let mut claimants = vec![0; self.lines.len()];
+        for (_, lines) in claims {
+            for &line in *lines {
+                *claimants
+                    .get_mut(line)
+                    .ok_or(BillingError::NoSuchLine(line))? += 1;
+            }
+        }
+        if let Some(line) = claimants.iter().position(|&count| count == 0) {
+            return Err(BillingError::UnclaimedLine(line));
+        }
+
+        // Each line's full cost split evenly between its claimants
+        let mut shares = vec![0; claims.len()];
+        for (i, cost) in self.line_costs().into_iter().enumerate() {
+            let sharing: Vec<usize> = claims
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, lines))| lines.contains(&i))
+                .map(|(payer, _)| payer)
+                .collect();
+            let parts = allocate(cost, &vec![1; sharing.len()]);
+            for (payer, part) in sharing.into_iter().zip(parts) {
+                shares[payer] += part;
+            }
+        }
+        let payers: Vec<&str> = claims.iter().map(|(payer, _)| *payer).collect();
+        Ok(payments(&payers, shares))
+    }
+
+    pub fn receipt(&self, payment: &Payment) -> Receipt {
+        let mut lines: Vec<(String, Money)> = self
+            .lines
+            .iter()
+            .map(|line| (format!("{} x {}", line.quantity, line.dish), line.amount))
+            .collect();
+        lines.push((String::from("Subtotal"), self.subtotal()));
+        for (label, off) in self.discounts() {
+            lines.push((label, -off));
+        }
+        if self.rules.service_charge > 0 {
+            let label = format!("Service {}", percent(self.rules.service_charge));
+            lines.push((label, self.service_charge()));
+        }
+        for (rule, (name, tax)) in self.rules.taxes.iter().zip(self.taxes()) {
+            lines.push((format!("{} {}", name, percent(rule.rate)), tax));
+        }
+        Receipt {
+            table: self.table,
+            lines,
+            total: self.total(),
+            payer: payment.payer.clone(),
+            paid: payment.amount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub payer: String,
+    pub amount: Money,
+}
+
+fn payments(payers: &[&str], shares: Vec<i64>) -> Vec<Payment> {
+    payers
+        .iter()
+        .zip(shares)
+        .map(|(payer, share)| Payment {
+            payer: payer.to_string(),
+            amount: Money::cents(share),
+        })
+        .collect()
+}
+
+// One payer's copy of the bill
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub table: u32,
+    pub lines: Vec<(String, Money)>,
+    pub total: Money,
+    pub payer: String,
+    pub paid: Money,
+}
+
+impl fmt::Display for Receipt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Table {}", self.table)?;
+        for (label, amount) in &self.lines {
+            writeln!(f, "{:<22}{:>10}", label, amount)?;
+        }
+        writeln!(f, "{:<22}{:>10}", "Total", self.total)?;
+        write!(
+            f,
+            "{:<22}{:>10}",
+            format!("Paid by {}", self.payer),
+            self.paid
+        )
+    }
+}
+
+// Takes the payments for a served order. They have to be for this
+// order's own bill and cover it exactly; if they do, the order is paid
+// and everyone gets a receipt. This is the only way to pay an order.
+pub fn settle(
+    order: &mut Order,
+    bill: &Bill,
+    payments: &[Payment],
+) -> Result<Vec<Receipt>, BillingError> {
+    if !bill.is_for(order) {
+        return Err(BillingError::NotThisOrder {
+            bill_table: bill.table,
+            order_table: order.table,
+        });
+    }
+    let paid: Money = payments.iter().map(|payment| payment.amount).sum();
+    if paid != bill.total() {
+        return Err(BillingError::WrongAmount {
+            expected: bill.total(),
+            paid,
+        });
+    }
+    serving::take_payment(order)?;
+    Ok(payments
+        .iter()
+        .map(|payment| bill.receipt(payment))
+        .collect())
+}
+
+// A line item's price as Money, if it isn't too big to be Money at all
+fn line_amount(item: &LineItem) -> Result<Money, BillingError> {
+    item.total()
+        .and_then(|total| i64::try_from(total).ok())
+        .map(Money::cents)
+        .ok_or_else(|| BillingError::TooExpensive(item.dish.clone()))
+}
+
+fn check_discount(label: &str, discount: Discount) -> Result<(), BillingError> {
+    match discount {
+        Discount::Amount(amount) if amount < Money::default() => {
+            Err(BillingError::NegativeDiscount(String::from(label)))
+        }
+        _ => Ok(()),
+    }
+}
+
+// a / b, rounding halves away from zero
+fn round_div(a: i64, b: i64) -> i64 {
+    let half = b / 2;
+    if a >= 0 {
+        (a + half) / b
+    } else {
+        (a - half) / b
+    }
+}
+
+// Shares `total` out in proportion to `weights` so the shares add up to
+// exactly `total`: everyone gets their share rounded down, then the
+// cents left over go to whoever lost the most in the rounding (the
+// earliest, on a tie). With no weight at all it's shared evenly.
+fn allocate(total: i64, weights: &[i64]) -> Vec<i64> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let sum: i64 = weights.iter().sum();
+    let even = vec![1; weights.len()];
+    let (weights, sum) = if sum == 0 {
+        (&even[..], weights.len() as i64)
+    } else {
+        (weights, sum)
+    };
+
+    let mut shares: Vec<i64> = weights.iter().map(|w| total * w / sum).collect();
+    let mut left = total - shares.iter().sum::<i64>();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse((total * weights[i]) % sum));
+    for i in by_remainder.into_iter().cycle() {
+        if left == 0 {
+            break;
+        }
+        shares[i] += 1;
+        left -= 1;
+    }
+    shares
+}
+
+// 1000 basis points is "10%", 1250 is "12.5%"
+fn percent(basis_points: u32) -> String {
+    let whole = basis_points / 100;
+    let fraction = basis_points % 100;
+    if fraction == 0 {
+        format!("{}%", whole)
+    } else {
+        let fraction = format!("{:02}", fraction);
+        format!("{}.{}%", whole, fraction.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::front_of_house::serving::{take_order, LineItem, OrderStatus};
+
+    // 2 x Soup at $8.50, 1 x Wine at $12.00
+    fn order() -> Order {
+        take_order(
+            4,
+            vec![
+                LineItem::new("Soup", 2, 850),
+                LineItem::new("Wine", 1, 1200),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn rules() -> BillingRules {
+        BillingRules {
+            taxes: vec![
+                TaxRule::on_everything("GST", 1000),
+                TaxRule::on_dishes("Alcohol", 1250, &["wine"]),
+            ],
+            service_charge: 1000,
+        }
+    }
+
+    #[test]
+    fn money_prints_like_money() {
+        assert_eq!("$8.50", Money::cents(850).to_string());
+        assert_eq!("-$0.05", Money::cents(-5).to_string());
+        assert_eq!("    $12.00", format!("{:>10}", Money::cents(1200)));
+        // 12.5% of $0.04 is half a cent, which rounds up
+        assert_eq!(Money::cents(1), Money::cents(4).percent(1250));
+        assert_eq!("12.5%", percent(1250));
+        assert_eq!("10%", percent(1000));
+    }
+
+    #[test]
+    fn taxes_and_service_go_on_top() {
+        let bill = Bill::for_order(&order(), &rules()).unwrap();
+        assert_eq!(Money::cents(2900), bill.subtotal());
+        assert_eq!(Money::cents(290), bill.service_charge());
+        assert_eq!(
+            vec![
+                (String::from("GST"), Money::cents(290)),
+                (String::from("Alcohol"), Money::cents(150)),
+            ],
+            bill.taxes()
+        );
+        assert_eq!(Money::cents(3630), bill.total());
+    }
+
+    #[test]
+    fn discounts_come_off_before_tax() {
+        let mut bill = Bill::for_order(&order(), &rules()).unwrap();
+        bill.discount("Happy hour", Discount::Percent(1000))
+            .unwrap();
+        bill.apply_coupon(&Coupon {
+            code: String::from("SOUP1"),
+            discount: Discount::Amount(Money::cents(100)),
+            min_spend: Money::cents(2000),
+        })
+        .unwrap();
+        assert_eq!(
+            vec![
+                (String::from("Happy hour"), Money::cents(290)),
+                (String::from("Coupon SOUP1"), Money::cents(100)),
+            ],
+            bill.discounts()
+        );
+        // $25.10 of food is left, the $3.90 off shared out in proportion:
+        // $2.29 off the soup, $1.61 off the wine
+        assert_eq!(Money::cents(251), bill.service_charge());
+        assert_eq!(Money::cents(251), bill.taxes()[0].1);
+        assert_eq!(Money::cents(130), bill.taxes()[1].1);
+        assert_eq!(Money::cents(2510 + 251 + 251 + 130), bill.total());
+    }
+
+    #[test]
+    fn coupons_have_conditions() {
+        let mut bill = Bill::for_order(&order(), &rules()).unwrap();
+        let big_spender = Coupon {
+            code: String::from("BIG"),
+            discount: Discount::Percent(5000),
+            min_spend: Money::cents(5000),
+        };
+        assert_eq!(
+            Err(BillingError::BelowMinimumSpend {
+                code: String::from("BIG"),
+                min_spend: Money::cents(5000)
+            }),
+            bill.apply_coupon(&big_spender)
+        );
+        let free = Coupon {
+            code: String::from("FREE"),
+            discount: Discount::Amount(Money::cents(10_000)),
+            min_spend: Money::default(),
+        };
+        bill.apply_coupon(&free).unwrap();
+        assert_eq!(
+            Err(BillingError::CouponAlreadyUsed(String::from("FREE"))),
+            bill.apply_coupon(&free)
+        );
+        // Never below zero
+        assert_eq!(Money::default(), bill.total());
+    }
+
+    #[test]
+    fn discounts_only_take_money_off() {
+        let mut bill = Bill::for_order(&order(), &rules()).unwrap();
+        assert_eq!(
+            Err(BillingError::NegativeDiscount(String::from("Oops"))),
+            bill.discount("Oops", Discount::Amount(Money::cents(-500)))
+        );
+        let sneaky = Coupon {
+            code: String::from("MORE"),
+            discount: Discount::Amount(Money::cents(-500)),
+            min_spend: Money::default(),
+        };
+        assert_eq!(
+            Err(BillingError::NegativeDiscount(String::from("MORE"))),
+            bill.apply_coupon(&sneaky)
+        );
+        assert!(bill.discounts().is_empty());
+        assert_eq!(Money::cents(3630), bill.total());
+    }
+
+    #[test]
+    fn prices_too_big_for_money_are_refused() {
+        // Three of them is more than a u64
+        let order = take_order(1, vec![LineItem::new("Caviar", 3, u64::MAX / 2)]).unwrap();
+        assert_eq!(
+            Err(BillingError::TooExpensive(String::from("Caviar"))),
+            Bill::for_order(&order, &rules())
+        );
+        // Fits in a u64 but not in Money
+        let order = take_order(1, vec![LineItem::new("Caviar", 1, u64::MAX / 2 + 1)]).unwrap();
+        assert!(Bill::for_order(&order, &rules()).is_err());
+        let half = i64::MAX as u64 / 2 + 1;
+        let order = take_order(
+            2,
+            vec![
+                LineItem::new("Caviar", 1, half),
+                LineItem::new("Truffle", 1, half),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(BillingError::TooExpensive(String::from("table 2"))),
+            Bill::for_order(&order, &rules())
+        );
+    }
+
+    #[test]
+    fn splits_evenly_to_the_cent() {
+        let bill = Bill::for_order(&order(), &rules()).unwrap();
+        let payments = bill.split_evenly(&["Ann", "Bob", "Cat", "Dan"]).unwrap();
+        let amounts: Vec<i64> = payments.iter().map(|p| p.amount.as_cents()).collect();
+        // $36.30 doesn't go into four; the first two pay the odd cents
+        assert_eq!(vec![908, 908, 907, 907], amounts);
+        assert_eq!(Err(BillingError::NoPayers), bill.split_evenly(&[]));
+    }
+
+    #[test]
+    fn splits_by_what_people_had() {
+        let bill = Bill::for_order(&order(), &rules()).unwrap();
+        // Ann and Bob share the soup, Bob had the wine
+        let payments = bill
+            .split_by_item(&[("Ann", &[0]), ("Bob", &[0, 1])])
+            .unwrap();
+        assert_eq!(Money::cents(1020), payments[0].amount);
+        assert_eq!(Money::cents(2610), payments[1].amount);
+        let total: Money = payments.iter().map(|p| p.amount).sum();
+        assert_eq!(bill.total(), total);
+
+        // Ann only had soup, so none of the alcohol tax is hers: $17.00
+        // of soup, $1.70 service and $1.70 GST
+        let payments = bill.split_by_item(&[("Ann", &[0]), ("Bob", &[1])]).unwrap();
+        assert_eq!(Money::cents(2040), payments[0].amount);
+        // $12.00 of wine, $1.20 service, $1.20 GST and $1.50 alcohol tax
+        assert_eq!(Money::cents(1590), payments[1].amount);
+
+        assert_eq!(
+            Err(BillingError::UnclaimedLine(1)),
+            bill.split_by_item(&[("Ann", &[0])])
+        );
+        assert_eq!(
+            Err(BillingError::NoSuchLine(7)),
+            bill.split_by_item(&[("Ann", &[0, 1, 7])])
+        );
+    }
+
+    #[test]
+    fn settling_pays_the_order_and_prints_receipts() {
+        let mut order = order();
+        order.start_cooking().unwrap();
+        order.finish_cooking().unwrap();
+        serving::serve_order(&mut order).unwrap();
+
+        let mut bill = Bill::for_order(&order, &rules()).unwrap();
+        bill.discount("Regulars", Discount::Amount(Money::cents(100)))
+            .unwrap();
+        let short = [Payment {
+            payer: String::from("Ann"),
+            amount: Money::cents(100),
+        }];
+        assert!(matches!(
+            settle(&mut order, &bill, &short),
+            Err(BillingError::WrongAmount { .. })
+        ));
+        assert_eq!(OrderStatus::Served, order.status());
+
+        // Someone else's bill won't do, even if it's paid in full
+        let other =
+            Bill::for_order(&take_order(3, order.items().to_vec()).unwrap(), &rules()).unwrap();
+        let payments = other.split_evenly(&["Ann"]).unwrap();
+        assert_eq!(
+            Err(BillingError::NotThisOrder {
+                bill_table: 3,
+                order_table: 4
+            }),
+            settle(&mut order, &other, &payments)
+        );
+        // Same table, but not what they ate
+        let smaller = take_order(4, vec![LineItem::new("Soup", 1, 850)]).unwrap();
+        let smaller = Bill::for_order(&smaller, &rules()).unwrap();
+        let payments = smaller.split_evenly(&["Ann"]).unwrap();
+        assert!(matches!(
+            settle(&mut order, &smaller, &payments),
+            Err(BillingError::NotThisOrder { .. })
+        ));
+        assert_eq!(OrderStatus::Served, order.status());
+
+        let payments = bill.split_evenly(&["Ann", "Bob"]).unwrap();
+        let receipts = settle(&mut order, &bill, &payments).unwrap();
+        assert_eq!(OrderStatus::Paid, order.status());
+        assert_eq!(
+            "Table 4\n\
+             2 x Soup                  $17.00\n\
+             1 x Wine                  $12.00\n\
+             Subtotal                  $29.00\n\
+             Regulars                  -$1.00\n\
+             Service 10%                $2.80\n\
+             GST 10%                    $2.80\n\
+             Alcohol 12.5%              $1.45\n\
+             Total                     $35.05\n\
+             Paid by Bob               $17.52",
+            receipts[1].to_string()
+        );
+        // Paying twice is an order error
+        assert_eq!(
+            Err(BillingError::Order(OrderError::IllegalTransition {
+                from: OrderStatus::Paid,
+                to: OrderStatus::Paid
+            })),
+            settle(&mut order, &bill, &payments)
+        );
+    }
+
+    #[test]
+    fn allocation_adds_up() {
+        assert_eq!(vec![34, 33, 33], allocate(100, &[1, 1, 1]));
+        assert_eq!(vec![0, 0], allocate(0, &[5, 0]));
+        assert_eq!(vec![50, 50], allocate(100, &[0, 0]));
+        // 2 : 1 of 10 is 6.67 : 3.33
+        assert_eq!(vec![7, 3], allocate(10, &[2, 1]));
+    }
+}
@@ -1,2 +1,505 @@
-pub fn add_to_waitlist() {}
-pub fn seat_at_table() {}
+pub mod reservations;
+
+use std::fmt;
+
+// The host keeps the waitlist and decides who sits where.
+//
+// Time is counted in whole minutes since the doors opened; the host
+// doesn't need to know what the clock on the wall says.
+pub type Minute = u32;
+pub type TableId = u32;
+
+// The most tables we'll push together for one party. Past that the
+// party is sitting in a corridor, not at a table.
+const MAX_COMBINED: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Regular,
+    // Jumps the queue, but not other VIPs
+    Vip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Party {
+    pub name: String,
+    pub size: u32,
+    pub priority: Priority,
+    // Only set on the stand-in party estimate_wait plays the evening
+    // forward with, so it can't be mistaken for a real one
+    probe: bool,
+}
+
+impl Party {
+    pub fn new(name: &str, size: u32) -> Party {
+        Party {
+            name: String::from(name),
+            size,
+            priority: Priority::Regular,
+            probe: false,
+        }
+    }
+
+    pub fn vip(mut self) -> Party {
+        self.priority = Priority::Vip;
+        self
+    }
+}
+
+// Tables in the same section sit close enough to be pushed together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub id: TableId,
+    pub capacity: u32,
+    pub section: String,
+}
+
+impl Table {
+    pub fn new(id: TableId, capacity: u32, section: &str) -> Table {
+        Table {
+            id,
+            capacity,
+            section: String::from(section),
+        }
+    }
+}
+
+// A party at its table (or tables) and when they'll be done
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seating {
+    pub party: Party,
+    pub tables: Vec<TableId>,
+    pub seated_at: Minute,
+    pub leaves_at: Minute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostingError {
+    EmptyParty,
+    // No table, or tables we could put together, would ever seat them
+    TooLarge(u32),
+    // Two parties under one name would make calling them out confusing
+    AlreadyWaiting(String),
+    // ...and would mean not knowing who's leaving when one of them does
+    AlreadySeated(String),
+}
+
+impl fmt::Display for HostingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HostingError::EmptyParty => write!(f, "a party needs at least one person"),
+            HostingError::TooLarge(size) => write!(f, "no tables can seat a party of {}", size),
+            HostingError::AlreadyWaiting(name) => {
+                write!(f, "there's already a party called {} waiting", name)
+            }
+            HostingError::AlreadySeated(name) => {
+                write!(f, "there's already a party called {} seated", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HostingError {}
+
+#[derive(Debug, Clone)]
+pub struct Host {
+    tables: Vec<Table>,
+    // How long a party keeps its table
+    meal_length: Minute,
+    // In the order they'll be seated
+    waitlist: Vec<Party>,
+    seated: Vec<Seating>,
+}
+
+impl Host {
+    pub fn new(tables: Vec<Table>, meal_length: Minute) -> Host {
+        Host {
+            tables,
+            meal_length,
+            waitlist: Vec::new(),
+            seated: Vec::new(),
+        }
+    }
+
+    // Puts the party in line and says how many parties are ahead of them.
+    // VIPs go in after any VIPs already waiting but before everyone else.
+    pub fn add_to_waitlist(&mut self, party: Party) -> Result<usize, HostingError> {
+        if party.size == 0 {
+            return Err(HostingError::EmptyParty);
+        }
+        if !self.could_ever_seat(party.size) {
+            return Err(HostingError::TooLarge(party.size));
+        }
+        if self.waitlist.iter().any(|p| p.name == party.name) {
+            return Err(HostingError::AlreadyWaiting(party.name));
+        }
+        if self.seated.iter().any(|s| s.party.name == party.name) {
+            return Err(HostingError::AlreadySeated(party.name));
+        }
+        Ok(self.join(party))
+    }
+
+    // Puts the party in line without asking any questions
+    fn join(&mut self, party: Party) -> usize {
+        let ahead = self
+            .waitlist
+            .iter()
+            .take_while(|p| p.priority >= party.priority)
+            .count();
+        self.waitlist.insert(ahead, party);
+        ahead
+    }
+
+    pub fn waiting(&self) -> &[Party] {
+        &self.waitlist
+    }
+
+    pub fn seated(&self) -> &[Seating] {
+        &self.seated
+    }
+
+    // Tables nobody's sitting at, in layout order
+    pub fn free_tables(&self) -> Vec<TableId> {
+        self.tables
+            .iter()
+            .filter(|table| !self.is_taken(table.id))
+            .map(|table| table.id)
+            .collect()
+    }
+
+    // Clears the tables of everyone who's finished by `now`, then seats
+    // whoever can be seated, in waitlist order. A party that doesn't fit
+    // anywhere yet doesn't hold up smaller parties behind it. Returns the
+    // parties that just sat down.
+    pub fn seat_at_table(&mut self, now: Minute) -> Vec<Seating> {
+        self.seated.retain(|seating| seating.leaves_at > now);
+
+        let mut just_seated = Vec::new();
+        let mut i = 0;
+        while i < self.waitlist.len() {
+            match self.best_fit(self.waitlist[i].size) {
+                Some(tables) => {
+                    let seating = Seating {
+                        party: self.waitlist.remove(i),
+                        tables,
+                        seated_at: now,
+                        leaves_at: now + self.meal_length,
+                    };
+                    self.seated.push(seating.clone());
+                    just_seated.push(seating);
+                }
+                None => i += 1,
+            }
+        }
+        just_seated
+    }
+
+    // For parties who leave early (or skip dessert)
+    pub fn leave(&mut self, name: &str) -> Option<Seating> {
+        let i = self.seated.iter().position(|s| s.party.name == name)?;
+        Some(self.seated.remove(i))
+    }
+
+    // Gives up their place in line
+    pub fn cancel(&mut self, name: &str) -> Option<Party> {
+        let i = self.waitlist.iter().position(|p| p.name == name)?;
+        Some(self.waitlist.remove(i))
+    }
+
+    // How long a party of this size joining the waitlist at `now` would
+    // wait, if nobody else turns up. We find out by playing the evening
+    // forward on a copy of the host, so the estimate always agrees with
+    // how seat_at_table really behaves. None if they could never be
+    // seated.
+    pub fn estimate_wait(&self, size: u32, priority: Priority, now: Minute) -> Option<Minute> {
+        if size == 0 || !self.could_ever_seat(size) {
+            return None;
+        }
+        let mut future = self.clone();
+        // Whatever it's called, only the probe is marked as the probe
+        future.join(Party {
+            priority,
+            probe: true,
+            ..Party::new("", size)
+        });
+
+        let mut clock = now;
+        loop {
+            let seated = future.seat_at_table(clock);
+            if seated.iter().any(|s| s.party.probe) {
+                return Some(clock - now);
+            }
+            // Nothing changes until the next party gets up
+            clock = future.seated.iter().map(|s| s.leaves_at).min()?;
+        }
+    }
+
+    fn is_taken(&self, id: TableId) -> bool {
+        self.seated.iter().any(|s| s.tables.contains(&id))
+    }
+
+    // The free tables that fit the party best: one table if any will do,
+    // otherwise as few tables pushed together as possible, wasting as
+    // few seats as possible either way
+    fn best_fit(&self, size: u32) -> Option<Vec<TableId>> {
+        let free: Vec<&Table> = self
+            .tables
+            .iter()
+            .filter(|table| !self.is_taken(table.id))
+            .collect();
+        let mut best: Option<Vec<&Table>> = None;
+        for section in sections(&free) {
+            let in_section: Vec<&Table> = free
+                .iter()
+                .copied()
+                .filter(|table| table.section == section)
+                .collect();
+            let mut chosen = Vec::new();
+            fit_in(&in_section, size, &mut chosen, &mut best);
+        }
+        best.map(|tables| tables.iter().map(|table| table.id).collect())
+    }
+
+    // Whether the party would fit with every table free
+    fn could_ever_seat(&self, size: u32) -> bool {
+        let everything = Host::new(self.tables.clone(), self.meal_length);
+        everything.best_fit(size).is_some()
+    }
+}
+
+// Each section once, in the order they first appear
+fn sections<'a>(tables: &[&'a Table]) -> Vec<&'a str> {
+    let mut sections: Vec<&str> = Vec::new();
+    for table in tables {
+        if !sections.contains(&table.section.as_str()) {
+            sections.push(&table.section);
+        }
+    }
+    sections
+}
+
+fn capacity(tables: &[&Table]) -> u32 {
+    tables.iter().map(|table| table.capacity).sum()
+}
+
+// Fewer tables first, then fewer empty seats
+fn better(a: &[&Table], b: &[&Table]) -> bool {
+    (a.len(), capacity(a)) < (b.len(), capacity(b))
+}
+
+// Tries every combination of up to MAX_COMBINED of `tables`, keeping the
+// best one that seats `size` in `best`
+fn fit_in<'a>(
+    tables: &[&'a Table],
+    size: u32,
+    chosen: &mut Vec<&'a Table>,
+    best: &mut Option<Vec<&'a Table>>,
+) {
+    if capacity(chosen) >= size {
+        if best.as_ref().is_none_or(|best| better(chosen, best)) {
+            *best = Some(chosen.clone());
+        }
+        return;
+    }
+    if chosen.len() == MAX_COMBINED {
+        return;
+    }
+    for (i, table) in tables.iter().enumerate() {
+        chosen.push(table);
+        fit_in(&tables[i + 1..], size, chosen, best);
+        chosen.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two 2-tops and a 4-top by the window, a 6-top out the back
+    fn host() -> Host {
+        Host::new(
+            vec![
+                Table::new(1, 2, "window"),
+                Table::new(2, 2, "window"),
+                Table::new(3, 4, "window"),
+                Table::new(4, 6, "back"),
+            ],
+            60,
+        )
+    }
+
+    fn tables_of(seated: &[Seating], name: &str) -> Vec<TableId> {
+        seated
+            .iter()
+            .find(|s| s.party.name == name)
+            .map(|s| s.tables.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn vips_wait_behind_vips_only() {
+        let mut host = host();
+        assert_eq!(Ok(0), host.add_to_waitlist(Party::new("Ann", 2)));
+        assert_eq!(Ok(1), host.add_to_waitlist(Party::new("Bob", 2)));
+        assert_eq!(Ok(0), host.add_to_waitlist(Party::new("Cat", 2).vip()));
+        assert_eq!(Ok(1), host.add_to_waitlist(Party::new("Dan", 2).vip()));
+        let names: Vec<&str> = host.waiting().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(vec!["Cat", "Dan", "Ann", "Bob"], names);
+    }
+
+    #[test]
+    fn bad_parties_are_turned_away() {
+        let mut host = host();
+        assert_eq!(
+            Err(HostingError::EmptyParty),
+            host.add_to_waitlist(Party::new("Nobody", 0))
+        );
+        // The window seats 8 at most, the back 6
+        assert_eq!(
+            Err(HostingError::TooLarge(9)),
+            host.add_to_waitlist(Party::new("Team", 9))
+        );
+        host.add_to_waitlist(Party::new("Ann", 2)).unwrap();
+        assert_eq!(
+            Err(HostingError::AlreadyWaiting(String::from("Ann"))),
+            host.add_to_waitlist(Party::new("Ann", 3))
+        );
+        host.seat_at_table(0);
+        assert_eq!(
+            Err(HostingError::AlreadySeated(String::from("Ann"))),
+            host.add_to_waitlist(Party::new("Ann", 3))
+        );
+    }
+
+    #[test]
+    fn parties_get_the_smallest_table_that_fits() {
+        let mut host = host();
+        host.add_to_waitlist(Party::new("Pair", 2)).unwrap();
+        host.add_to_waitlist(Party::new("Trio", 3)).unwrap();
+        host.add_to_waitlist(Party::new("Five", 5)).unwrap();
+        let seated = host.seat_at_table(0);
+        assert_eq!(vec![1], tables_of(&seated, "Pair"));
+        assert_eq!(vec![3], tables_of(&seated, "Trio"));
+        assert_eq!(vec![4], tables_of(&seated, "Five"));
+        assert_eq!(vec![2], host.free_tables());
+    }
+
+    #[test]
+    fn tables_are_pushed_together_when_none_is_big_enough() {
+        let mut host = host();
+        host.add_to_waitlist(Party::new("Six", 6)).unwrap();
+        host.add_to_waitlist(Party::new("Seven", 7)).unwrap();
+        let seated = host.seat_at_table(0);
+        assert_eq!(vec![4], tables_of(&seated, "Six"));
+        // The back table's gone, so it's all three by the window
+        assert_eq!(vec![1, 2, 3], tables_of(&seated, "Seven"));
+    }
+
+    #[test]
+    fn big_parties_do_not_hold_up_small_ones() {
+        let mut host = host();
+        host.add_to_waitlist(Party::new("Couple", 2)).unwrap();
+        host.seat_at_table(0);
+        host.add_to_waitlist(Party::new("Eight", 8)).unwrap();
+        host.add_to_waitlist(Party::new("Solo", 1)).unwrap();
+        let seated = host.seat_at_table(5);
+        assert_eq!(1, seated.len());
+        assert_eq!("Solo", seated[0].party.name);
+        assert_eq!("Eight", host.waiting()[0].name);
+        // Once the window clears the eight get all of it
+        assert!(host.seat_at_table(64).is_empty());
+        assert_eq!(vec![1, 2, 3], tables_of(&host.seat_at_table(65), "Eight"));
+    }
+
+    #[test]
+    fn leaving_early_frees_the_table() {
+        let mut host = host();
+        host.add_to_waitlist(Party::new("Six", 6)).unwrap();
+        host.seat_at_table(0);
+        host.add_to_waitlist(Party::new("Other six", 6)).unwrap();
+        assert!(host.cancel("Nobody").is_none());
+        assert_eq!("Six", host.leave("Six").unwrap().party.name);
+        assert_eq!(vec![4], tables_of(&host.seat_at_table(10), "Other six"));
+    }
+
+    // Parties turn up through the evening; the host seats people every
+    // minute. Each arrival is told how long they should wait and we
+    // check how long they really did.
+    fn simulate(host: &mut Host, arrivals: &[(Minute, Party)]) -> Vec<(String, Minute, Minute)> {
+        let mut promised = Vec::new();
+        let mut waits = Vec::new();
+        for now in 0..=600 {
+            for (_, party) in arrivals.iter().filter(|(at, _)| *at == now) {
+                let estimate = host.estimate_wait(party.size, party.priority, now).unwrap();
+                promised.push((party.name.clone(), now, estimate));
+                host.add_to_waitlist(party.clone()).unwrap();
+            }
+            for seating in host.seat_at_table(now) {
+                let (_, arrived, estimate) = promised
+                    .iter()
+                    .find(|(name, _, _)| *name == seating.party.name)
+                    .unwrap();
+                waits.push((seating.party.name, *estimate, now - arrived));
+            }
+        }
+        assert!(host.waiting().is_empty(), "everyone gets seated eventually");
+        waits
+    }
+
+    #[test]
+    fn estimates_hold_when_nobody_cuts_in() {
+        let mut host = host();
+        let arrivals = [
+            (0, Party::new("A", 4)),
+            (0, Party::new("B", 6)),
+            (5, Party::new("C", 4)),
+            (10, Party::new("D", 2)),
+            (12, Party::new("E", 2)),
+            (20, Party::new("F", 3)),
+            (30, Party::new("G", 8)),
+        ];
+        let waits = simulate(&mut host, &arrivals);
+        assert_eq!(7, waits.len());
+        for (name, estimate, actual) in &waits {
+            assert_eq!(estimate, actual, "{} was told {}", name, estimate);
+        }
+        // C gets the two 2-tops pushed together, D waits for A's 4-top
+        // and F for C's tables
+        let wait_of = |name: &str| waits.iter().find(|w| w.0 == name).unwrap().2;
+        assert_eq!(0, wait_of("C"));
+        assert_eq!(50, wait_of("D"));
+        assert_eq!(45, wait_of("F"));
+    }
+
+    #[test]
+    fn vips_arriving_later_can_beat_the_estimate() {
+        let mut host = host();
+        let arrivals = [
+            (0, Party::new("Eight", 8)),
+            (1, Party::new("Seven", 7)),
+            (2, Party::new("Big shot", 7).vip()),
+        ];
+        let waits = simulate(&mut host, &arrivals);
+        let wait_of = |name: &str| waits.iter().find(|w| w.0 == name).unwrap().clone();
+        // Only the window seats seven. Seven was told 59 minutes, but the
+        // VIP got there first and Seven had to wait for them to finish.
+        assert_eq!((String::from("Seven"), 59, 119), wait_of("Seven"));
+        assert_eq!((String::from("Big shot"), 58, 58), wait_of("Big shot"));
+    }
+
+    #[test]
+    fn no_estimate_for_parties_that_never_fit() {
+        assert_eq!(None, host().estimate_wait(20, Priority::Regular, 0));
+        assert_eq!(Some(0), host().estimate_wait(2, Priority::Regular, 0));
+        assert_eq!(None, host().estimate_wait(0, Priority::Regular, 0));
+    }
+
+    #[test]
+    fn estimates_ignore_what_parties_are_called() {
+        let mut host = host();
+        // Nobody else has a name like this one - and it doesn't matter
+        host.add_to_waitlist(Party::new("", 2)).unwrap();
+        host.add_to_waitlist(Party::new("\0estimate", 2)).unwrap();
+        assert_eq!(Some(0), host.estimate_wait(2, Priority::Regular, 0));
+    }
+}
@@ -0,0 +1,652 @@
+use super::{Host, HostingError, Party};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+// Reservations: parties booking ahead for a time slot.
+//
+// Unlike the waitlist, which only lives for one evening, the book is
+// kept in a file so it survives the host's computer being turned off.
+// Every change is written straight back.
+
+pub type ReservationId = u32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookingRules {
+    // How many people the restaurant can seat at once
+    pub seats: u32,
+    // Bookings start on the hour and every this many minutes after
+    pub slot_minutes: u32,
+    // How long a booking holds its seats
+    pub sitting_minutes: u32,
+    // How late a party can be before we give their seats away
+    pub grace_minutes: u32,
+    // How early a party can check in before their booking starts
+    pub early_minutes: u32,
+    // The first and last times a booking can start
+    pub first_slot: NaiveTime,
+    pub last_slot: NaiveTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Booked,
+    Seated,
+    NoShow,
+    Cancelled,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Status::Booked => "booked",
+            Status::Seated => "seated",
+            Status::NoShow => "a no-show",
+            Status::Cancelled => "cancelled",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub id: ReservationId,
+    pub name: String,
+    pub size: u32,
+    pub start: NaiveDateTime,
+    pub status: Status,
+}
+
+impl Reservation {
+    // Still holding seats: booked and not given up on, or sitting down
+    fn holds_seats(&self) -> bool {
+        matches!(self.status, Status::Booked | Status::Seated)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReservationError {
+    Io(io::Error),
+    // The file's there but it isn't a reservation book
+    Corrupt(toml::de::Error),
+    Save(toml::ser::Error),
+    EmptyParty,
+    // Not on the slot grid, or outside the hours we take bookings for
+    NotASlot(NaiveDateTime),
+    Full {
+        start: NaiveDateTime,
+        seats_left: u32,
+    },
+    NotFound(ReservationId),
+    // Can't check in or cancel what isn't booked any more
+    NotBooked {
+        id: ReservationId,
+        status: Status,
+    },
+    // Turned up after the grace period; the seats have gone
+    TooLate(ReservationId),
+    // Turned up so early the booking isn't theirs to use yet
+    TooEarly(ReservationId),
+    // Checked in, but the host couldn't put them on the waitlist
+    Hosting(HostingError),
+}
+
+impl fmt::Display for ReservationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReservationError::Io(error) => {
+                write!(f, "couldn't use the reservation book: {}", error)
+            }
+            ReservationError::Corrupt(error) => {
+                write!(f, "couldn't understand the reservation book: {}", error)
+            }
+            ReservationError::Save(error) => {
+                write!(f, "couldn't save the reservation book: {}", error)
+            }
+            ReservationError::EmptyParty => write!(f, "a booking needs at least one person"),
+            ReservationError::NotASlot(start) => {
+                write!(f, "{} isn't a time we take bookings for", start)
+            }
+            ReservationError::Full { start, seats_left } => {
+                write!(f, "only {} seats are free at {}", seats_left, start)
+            }
+            ReservationError::NotFound(id) => write!(f, "there's no reservation {}", id),
+            ReservationError::NotBooked { id, status } => {
+                write!(f, "reservation {} is {}", id, status)
+            }
+            ReservationError::TooLate(id) => write!(f, "reservation {} has expired", id),
+            ReservationError::TooEarly(id) => write!(f, "reservation {} hasn't started yet", id),
+            ReservationError::Hosting(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ReservationError {}
+
+impl From<io::Error> for ReservationError {
+    fn from(error: io::Error) -> ReservationError {
+        ReservationError::Io(error)
+    }
+}
+
+impl From<HostingError> for ReservationError {
+    fn from(error: HostingError) -> ReservationError {
+        ReservationError::Hosting(error)
+    }
+}
+
+impl From<toml::de::Error> for ReservationError {
+    fn from(error: toml::de::Error) -> ReservationError {
+        ReservationError::Corrupt(error)
+    }
+}
+
+impl From<toml::ser::Error> for ReservationError {
+    fn from(error: toml::ser::Error) -> ReservationError {
+        ReservationError::Save(error)
+    }
+}
+
+// What goes in the file
+#[derive(Default, Serialize, Deserialize)]
+struct BookFile {
+    next_id: ReservationId,
+    #[serde(default, rename = "reservation")]
+    reservations: Vec<Reservation>,
+}
+
+pub struct ReservationBook {
+    rules: BookingRules,
+    // None for a book that's only kept in memory
+    path: Option<PathBuf>,
+    next_id: ReservationId,
+    reservations: Vec<Reservation>,
+}
+
+impl ReservationBook {
+    // A book that's forgotten when it's dropped
+    pub fn new(rules: BookingRules) -> ReservationBook {
+        ReservationBook {
+            rules,
+            path: None,
+            next_id: 1,
+            reservations: Vec::new(),
+        }
+    }
+
+    // The book kept in `path`, starting a new one if there's no file yet
+    pub fn open(
+        path: impl AsRef<Path>,
+        rules: BookingRules,
+    ) -> Result<ReservationBook, ReservationError> {
+        let path = path.as_ref().to_path_buf();
+        let file: BookFile = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => BookFile::default(),
+            Err(error) => return Err(error.into()),
+        };
+        Ok(ReservationBook {
+            rules,
+            path: Some(path),
+            next_id: file.next_id.max(1),
+            reservations: file.reservations,
+        })
+    }
+
+    pub fn get(&self, id: ReservationId) -> Option<&Reservation> {
+        self.reservations.iter().find(|r| r.id == id)
+    }
+
+    // Everything booked for one day, earliest first
+    pub fn on(&self, day: NaiveDate) -> Vec<&Reservation> {
+        let mut bookings: Vec<&Reservation> = self
+            .reservations
+            .iter()
+            .filter(|r| r.start.date() == day)
+            .collect();
+        bookings.sort_by_key(|r| r.start);
+        bookings
+    }
+
+    pub fn book(
+        &mut self,
+        name: &str,
+        size: u32,
+        start: NaiveDateTime,
+    ) -> Result<Reservation, ReservationError> {
+        if size == 0 {
+            return Err(ReservationError::EmptyParty);
+        }
+        if !self.is_slot(start) {
+            return Err(ReservationError::NotASlot(start));
+        }
+        let seats_left = self.seats_left(start);
+        if size > seats_left {
+            return Err(ReservationError::Full { start, seats_left });
+        }
+
+        let mut draft = self.draft();
+        let reservation = Reservation {
+            id: draft.next_id,
+            name: String::from(name),
+            size,
+            start,
+            status: Status::Booked,
+        };
+        draft.next_id += 1;
+        draft.reservations.push(reservation.clone());
+        self.commit(draft)?;
+        Ok(reservation)
+    }
+
+    pub fn cancel(&mut self, id: ReservationId) -> Result<Reservation, ReservationError> {
+        self.change(id, |_| Ok(Status::Cancelled))
+    }
+
+    // The party's here. They go on the host's waitlist as a VIP, since
+    // they booked, and we say how many parties are ahead of them - see
+    // Host::add_to_waitlist. If the host can't take them, say because a
+    // walk-in already has their name, the reservation stays booked so
+    // they can check in again under another name.
+    pub fn check_in(
+        &mut self,
+        id: ReservationId,
+        now: NaiveDateTime,
+        host: &mut Host,
+    ) -> Result<usize, ReservationError> {
+        let (early, grace) = (self.early(), self.grace());
+        let mut joined = None;
+        let checked_in = self.change(id, |r| {
+            if now < r.start - early {
+                return Err(ReservationError::TooEarly(id));
+            }
+            if now > r.start + grace {
+                return Err(ReservationError::TooLate(id));
+            }
+            let ahead = host.add_to_waitlist(Party::new(&r.name, r.size).vip())?;
+            joined = Some((ahead, r.name.clone()));
+            Ok(Status::Seated)
+        });
+        match (checked_in, joined) {
+            (Ok(_), Some((ahead, _))) => Ok(ahead),
+            // The book couldn't be saved, so they aren't checked in after
+            // all; take them off the waitlist again
+            (Err(error), Some((_, name))) => {
+                host.cancel(&name);
+                Err(error)
+            }
+            (Err(error), None) => Err(error),
+            (Ok(_), None) => unreachable!("only checked in after joining the waitlist"),
+        }
+    }
+
+    // Gives away the seats of everyone more than the grace period late.
+    // Returns who they were.
+    pub fn expire_no_shows(
+        &mut self,
+        now: NaiveDateTime,
+    ) -> Result<Vec<Reservation>, ReservationError> {
+        let grace = self.grace();
+        let mut draft = self.draft();
+        let mut expired = Vec::new();
+        for reservation in &mut draft.reservations {
+            if reservation.status == Status::Booked && now > reservation.start + grace {
+                reservation.status = Status::NoShow;
+                expired.push(reservation.clone());
+            }
+        }
+        if !expired.is_empty() {
+            self.commit(draft)?;
+        }
+        Ok(expired)
+    }
+
+    // The slots on `day` a party of `size` could still book
+    pub fn available_slots(&self, day: NaiveDate, size: u32) -> Vec<NaiveDateTime> {
+        let step = Duration::minutes(i64::from(self.rules.slot_minutes.max(1)));
+        let last = day.and_time(self.rules.last_slot);
+        let mut slot = day.and_time(self.rules.first_slot);
+        let mut slots = Vec::new();
+        while slot <= last {
+            if self.is_slot(slot) && self.seats_left(slot) >= size {
+                slots.push(slot);
+            }
+            slot += step;
+        }
+        slots
+    }
+
+    // The fewest seats free at any moment of a sitting starting at
+    // `start`. The busiest moment is always when some booking starts, so
+    // those are the only moments we need to look at.
+    pub fn seats_left(&self, start: NaiveDateTime) -> u32 {
+        let end = start + self.sitting();
+        let overlapping: Vec<&Reservation> = self
+            .reservations
+            .iter()
+            .filter(|r| r.holds_seats() && r.start < end && start < r.start + self.sitting())
+            .collect();
+        let moments = std::iter::once(start).chain(
+            overlapping
+                .iter()
+                .map(|r| r.start)
+                .filter(|moment| *moment > start),
+        );
+        let busiest = moments
+            .map(|moment| {
+                overlapping
+                    .iter()
+                    .filter(|r| r.start <= moment && moment < r.start + self.sitting())
+                    .map(|r| r.size)
+                    .sum::<u32>()
+            })
+            .max()
+            .unwrap_or(0);
+        self.rules.seats.saturating_sub(busiest)
+    }
+
+    fn is_slot(&self, start: NaiveDateTime) -> bool {
+        let time = start.time();
+        let minutes = time.hour() * 60 + time.minute();
+        time.second() == 0
+            && time.nanosecond() == 0
+            && minutes.is_multiple_of(self.rules.slot_minutes.max(1))
+            && self.rules.first_slot <= time
+            && time <= self.rules.last_slot
+    }
+
+    fn sitting(&self) -> Duration {
+        Duration::minutes(i64::from(self.rules.sitting_minutes))
+    }
+
+    fn early(&self) -> Duration {
+        Duration::minutes(i64::from(self.rules.early_minutes))
+    }
+
+    fn grace(&self) -> Duration {
+        Duration::minutes(i64::from(self.rules.grace_minutes))
+    }
+
+    // Moves a booked reservation on to whatever `next` says, and saves
+    fn change(
+        &mut self,
+        id: ReservationId,
+        next: impl FnOnce(&Reservation) -> Result<Status, ReservationError>,
+    ) -> Result<Reservation, ReservationError> {
+        let mut draft = self.draft();
+        let reservation = draft
+            .reservations
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or(ReservationError::NotFound(id))?;
+        if reservation.status != Status::Booked {
+            return Err(ReservationError::NotBooked {
+                id,
+                status: reservation.status,
+            });
+        }
+        reservation.status = next(reservation)?;
+        let reservation = reservation.clone();
+        self.commit(draft)?;
+        Ok(reservation)
+    }
+
+    // A copy of the book to make changes to. Nothing happens to the
+    // book itself until the copy is committed.
+    fn draft(&self) -> BookFile {
+        BookFile {
+            next_id: self.next_id,
+            reservations: self.reservations.clone(),
+        }
+    }
+
+    // Saves `draft` and only then makes it the book, so when the save
+    // fails the book is just as it was before. The file is written to a
+    // scratch file first and then swapped in, so a crash halfway through
+    // can't leave half a book behind either.
+    fn commit(&mut self, draft: BookFile) -> Result<(), ReservationError> {
+        if let Some(path) = &self.path {
+            let scratch = path.with_extension("tmp");
+            fs::write(&scratch, toml::to_string(&draft)?)?;
+            fs::rename(&scratch, path)?;
+        }
+        self.next_id = draft.next_id;
+        self.reservations = draft.reservations;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::front_of_house::hosting::Table;
+    use std::{env, process};
+
+    fn rules() -> BookingRules {
+        BookingRules {
+            seats: 10,
+            slot_minutes: 30,
+            sitting_minutes: 90,
+            grace_minutes: 15,
+            early_minutes: 30,
+            first_slot: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            last_slot: NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+        }
+    }
+
+    fn host() -> Host {
+        Host::new(vec![Table::new(1, 10, "main")], 90)
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 7, 1)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    // A file of our own in the temp directory, gone when we're done
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            let path = env::temp_dir().join(format!("{}-{}.toml", name, process::id()));
+            let _ = fs::remove_file(&path);
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn overlapping_bookings_share_the_seats() {
+        let mut book = ReservationBook::new(rules());
+        book.book("Ann", 6, at(18, 0)).unwrap();
+        // 18:00 until 19:30 holds Ann's six, so 19:00 overlaps
+        assert_eq!(4, book.seats_left(at(19, 0)));
+        book.book("Bob", 4, at(19, 0)).unwrap();
+        assert!(matches!(
+            book.book("Cat", 2, at(18, 30)),
+            Err(ReservationError::Full { seats_left: 0, .. })
+        ));
+        // Ann's gone by 19:30 but Bob's still there
+        assert_eq!(6, book.seats_left(at(19, 30)));
+        // Bob's there until 20:30
+        assert_eq!(6, book.seats_left(at(20, 0)));
+        assert_eq!(10, book.seats_left(at(20, 30)));
+    }
+
+    #[test]
+    fn bookings_go_in_slots() {
+        let mut book = ReservationBook::new(rules());
+        assert!(matches!(
+            book.book("Ann", 2, at(18, 10)),
+            Err(ReservationError::NotASlot(_))
+        ));
+        assert!(matches!(
+            book.book("Ann", 2, at(17, 30)),
+            Err(ReservationError::NotASlot(_))
+        ));
+        assert!(matches!(
+            book.book("Ann", 0, at(18, 0)),
+            Err(ReservationError::EmptyParty)
+        ));
+
+        book.book("Big table", 8, at(19, 0)).unwrap();
+        let day = at(0, 0).date();
+        // No sitting that runs into the big table's 19:00 to 20:30 has
+        // room for four
+        assert_eq!(vec![at(20, 30), at(21, 0)], book.available_slots(day, 4));
+        assert_eq!(7, book.available_slots(day, 2).len());
+    }
+
+    #[test]
+    fn no_shows_give_their_seats_back() {
+        let mut book = ReservationBook::new(rules());
+        let mut host = host();
+        let ann = book.book("Ann", 10, at(18, 0)).unwrap();
+        let bob = book.book("Bob", 10, at(20, 0)).unwrap();
+        assert_eq!(0, book.seats_left(at(18, 30)));
+
+        assert!(book.expire_no_shows(at(18, 15)).unwrap().is_empty());
+        let expired = book.expire_no_shows(at(18, 16)).unwrap();
+        assert_eq!(
+            vec![ann.id],
+            expired.iter().map(|r| r.id).collect::<Vec<_>>()
+        );
+        assert_eq!(10, book.seats_left(at(18, 30)));
+        assert!(matches!(
+            book.check_in(ann.id, at(18, 20), &mut host),
+            Err(ReservationError::NotBooked {
+                status: Status::NoShow,
+                ..
+            })
+        ));
+        // Bob's a little late but inside the grace period
+        assert_eq!(0, book.check_in(bob.id, at(20, 10), &mut host).unwrap());
+        assert_eq!(&[Party::new("Bob", 10).vip()], host.waiting());
+        assert_eq!(Status::Seated, book.get(bob.id).unwrap().status);
+        // Seated parties still need their seats
+        assert_eq!(0, book.seats_left(at(20, 30)));
+    }
+
+    #[test]
+    fn late_arrivals_and_cancellations() {
+        let mut book = ReservationBook::new(rules());
+        let ann = book.book("Ann", 2, at(18, 0)).unwrap();
+        assert!(matches!(
+            book.check_in(ann.id, at(18, 30), &mut host()),
+            Err(ReservationError::TooLate(_))
+        ));
+        assert_eq!(Status::Booked, book.get(ann.id).unwrap().status);
+        assert_eq!(Status::Cancelled, book.cancel(ann.id).unwrap().status);
+        assert!(matches!(
+            book.cancel(ann.id),
+            Err(ReservationError::NotBooked { .. })
+        ));
+        assert!(matches!(
+            book.cancel(99),
+            Err(ReservationError::NotFound(99))
+        ));
+        assert_eq!(10, book.seats_left(at(18, 0)));
+    }
+
+    #[test]
+    fn early_arrivals_and_name_clashes() {
+        let mut book = ReservationBook::new(rules());
+        let mut host = host();
+        let bob = book.book("Bob", 2, at(20, 0)).unwrap();
+        assert!(matches!(
+            book.check_in(bob.id, at(19, 29), &mut host),
+            Err(ReservationError::TooEarly(_))
+        ));
+        assert_eq!(Status::Booked, book.get(bob.id).unwrap().status);
+
+        // A walk-in got there first with the same name
+        host.add_to_waitlist(Party::new("Bob", 4)).unwrap();
+        assert!(matches!(
+            book.check_in(bob.id, at(19, 30), &mut host),
+            Err(ReservationError::Hosting(HostingError::AlreadyWaiting(_)))
+        ));
+        assert_eq!(Status::Booked, book.get(bob.id).unwrap().status);
+        assert_eq!(1, host.waiting().len());
+
+        host.cancel("Bob");
+        assert_eq!(0, book.check_in(bob.id, at(19, 30), &mut host).unwrap());
+        assert_eq!(Status::Seated, book.get(bob.id).unwrap().status);
+    }
+
+    #[test]
+    fn the_book_survives_being_closed() {
+        let file = TempFile::new("reservations-survive");
+        let ann;
+        {
+            let mut book = ReservationBook::open(&file.0, rules()).unwrap();
+            ann = book.book("Ann", 4, at(19, 0)).unwrap();
+            book.book("Bob", 2, at(18, 0)).unwrap();
+            book.cancel(ann.id).unwrap();
+        }
+        let mut book = ReservationBook::open(&file.0, rules()).unwrap();
+        let names: Vec<&str> = book
+            .on(at(0, 0).date())
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(vec!["Bob", "Ann"], names);
+        assert_eq!(Status::Cancelled, book.get(ann.id).unwrap().status);
+        // Ids carry on where they left off
+        assert_eq!(3, book.book("Cat", 2, at(18, 0)).unwrap().id);
+    }
+
+    #[test]
+    fn a_failed_save_changes_nothing() {
+        let dir = env::temp_dir().join(format!("reservations-rollback-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut book = ReservationBook::open(dir.join("book.toml"), rules()).unwrap();
+        let ann = book.book("Ann", 4, at(18, 0)).unwrap();
+
+        // With the directory gone there's nowhere to save to
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            book.book("Bob", 6, at(18, 0)),
+            Err(ReservationError::Io(_))
+        ));
+        assert!(matches!(book.cancel(ann.id), Err(ReservationError::Io(_))));
+        assert!(matches!(
+            book.expire_no_shows(at(18, 30)),
+            Err(ReservationError::Io(_))
+        ));
+        // Nor does a check-in: they come off the waitlist again
+        let mut host = host();
+        assert!(matches!(
+            book.check_in(ann.id, at(18, 0), &mut host),
+            Err(ReservationError::Io(_))
+        ));
+        assert!(host.waiting().is_empty());
+        assert_eq!(6, book.seats_left(at(18, 0)));
+        assert_eq!(Status::Booked, book.get(ann.id).unwrap().status);
+
+        // Bob's failed booking didn't use up an id
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(2, book.book("Bob", 6, at(18, 0)).unwrap().id);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_broken_file_is_an_error() {
+        let file = TempFile::new("reservations-broken");
+        fs::write(&file.0, "this isn't [a book").unwrap();
+        assert!(matches!(
+            ReservationBook::open(&file.0, rules()),
+            Err(ReservationError::Corrupt(_))
+        ));
+    }
+}
@@ -1,3 +1,241 @@
-pub fn take_order() {}
-pub fn serve_order() {}
-pub fn take_payment() {}
+use std::fmt;
+
+// Everything an order goes through, in order. An order only ever moves
+// one step forward: it can't be served before it's been cooked, or paid
+// for twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    Placed,
+    Cooking,
+    Ready,
+    Served,
+    Paid,
+}
+
+impl OrderStatus {
+    // The step after this one, if there is one
+    pub fn next(self) -> Option<OrderStatus> {
+        match self {
+            OrderStatus::Placed => Some(OrderStatus::Cooking),
+            OrderStatus::Cooking => Some(OrderStatus::Ready),
+            OrderStatus::Ready => Some(OrderStatus::Served),
+            OrderStatus::Served => Some(OrderStatus::Paid),
+            OrderStatus::Paid => None,
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OrderStatus::Placed => "placed",
+            OrderStatus::Cooking => "cooking",
+            OrderStatus::Ready => "ready",
+            OrderStatus::Served => "served",
+            OrderStatus::Paid => "paid",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// One line on the order: so many of one dish. Prices are in cents so
+// adding them up never goes wrong the way floats can.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineItem {
+    pub dish: String,
+    pub quantity: u32,
+    pub unit_price: u64,
+}
+
+impl LineItem {
+    pub fn new(dish: &str, quantity: u32, unit_price: u64) -> LineItem {
+        LineItem {
+            dish: String::from(dish),
+            quantity,
+            unit_price,
+        }
+    }
+
+    // None if it's more cents than a u64 can count
+    pub fn total(&self) -> Option<u64> {
+        self.unit_price.checked_mul(u64::from(self.quantity))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    // Nothing to cook
+    EmptyOrder,
+    // "Zero soups, please"
+    NoQuantity(String),
+    // Skipping a step, going backwards or changing a finished order
+    IllegalTransition { from: OrderStatus, to: OrderStatus },
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderError::EmptyOrder => write!(f, "an order needs at least one item"),
+            OrderError::NoQuantity(dish) => write!(f, "ordered no {}", dish),
+            OrderError::IllegalTransition { from, to } => {
+                write!(f, "an order that's {} can't be {}", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub table: u32,
+    items: Vec<LineItem>,
+    status: OrderStatus,
+}
+
+impl Order {
+    pub fn items(&self) -> &[LineItem] {
+        &self.items
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        self.items
+            .iter()
+            .try_fold(0u64, |sum, item| sum.checked_add(item.total()?))
+    }
+
+    // Customers can keep adding to an order until the kitchen starts on it
+    pub fn add_item(&mut self, item: LineItem) -> Result<(), OrderError> {
+        if self.status != OrderStatus::Placed {
+            return Err(OrderError::IllegalTransition {
+                from: self.status,
+                to: OrderStatus::Placed,
+            });
+        }
+        if item.quantity == 0 {
+            return Err(OrderError::NoQuantity(item.dish));
+        }
+        self.items.push(item);
+        Ok(())
+    }
+
+    // The kitchen's half of the lifecycle
+    pub fn start_cooking(&mut self) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Cooking)
+    }
+
+    pub fn finish_cooking(&mut self) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Ready)
+    }
+
+    // Every change of status goes through here, so there's only one
+    // place that decides what's allowed
+    fn advance(&mut self, to: OrderStatus) -> Result<(), OrderError> {
+        if self.status.next() != Some(to) {
+            return Err(OrderError::IllegalTransition {
+                from: self.status,
+                to,
+            });
+        }
+        self.status = to;
+        Ok(())
+    }
+}
+
+pub fn take_order(table: u32, items: Vec<LineItem>) -> Result<Order, OrderError> {
+    if items.is_empty() {
+        return Err(OrderError::EmptyOrder);
+    }
+    let mut order = Order {
+        table,
+        items: Vec::new(),
+        status: OrderStatus::Placed,
+    };
+    for item in items {
+        order.add_item(item)?;
+    }
+    Ok(order)
+}
+
+pub fn serve_order(order: &mut Order) -> Result<(), OrderError> {
+    order.advance(OrderStatus::Served)
+}
+
+// Marks the order paid. How much was paid, after discounts and taxes,
+// is the bill's business, so only billing::settle gets to call this.
+pub(crate) fn take_payment(order: &mut Order) -> Result<(), OrderError> {
+    order.advance(OrderStatus::Paid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> Order {
+        take_order(
+            4,
+            vec![
+                LineItem::new("Soup", 2, 850),
+                LineItem::new("Salad", 1, 1200),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn an_order_goes_through_every_step() {
+        let mut order = order();
+        assert_eq!(OrderStatus::Placed, order.status());
+        order.start_cooking().unwrap();
+        order.finish_cooking().unwrap();
+        serve_order(&mut order).unwrap();
+        assert_eq!(OrderStatus::Served, order.status());
+        assert_eq!(Ok(()), take_payment(&mut order));
+        assert_eq!(OrderStatus::Paid, order.status());
+        assert_eq!(None, order.status().next());
+    }
+
+    #[test]
+    fn steps_cannot_be_skipped_or_repeated() {
+        let mut order = order();
+        assert_eq!(
+            Err(OrderError::IllegalTransition {
+                from: OrderStatus::Placed,
+                to: OrderStatus::Served
+            }),
+            serve_order(&mut order)
+        );
+        order.start_cooking().unwrap();
+        assert!(order.start_cooking().is_err());
+        assert!(take_payment(&mut order).is_err());
+        // Nothing changed along the way
+        assert_eq!(OrderStatus::Cooking, order.status());
+        assert_eq!(
+            "an order that's cooking can't be paid",
+            take_payment(&mut order).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn items_only_go_on_a_placed_order() {
+        let mut order = order();
+        order.add_item(LineItem::new("Bread", 1, 300)).unwrap();
+        assert_eq!(3, order.items().len());
+        assert_eq!(
+            Err(OrderError::NoQuantity(String::from("Soup"))),
+            order.add_item(LineItem::new("Soup", 0, 850))
+        );
+        order.start_cooking().unwrap();
+        assert!(order.add_item(LineItem::new("Bread", 1, 300)).is_err());
+        assert_eq!(Some(3200), order.total());
+    }
+
+    #[test]
+    fn empty_orders_are_refused() {
+        assert_eq!(Err(OrderError::EmptyOrder), take_order(1, Vec::new()));
+    }
+}
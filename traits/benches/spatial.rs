@@ -0,0 +1,82 @@
+// How much the R-tree in SpatialIndex saves over checking every shape.
+// Run with `cargo bench`.
+
+use cool_lib::{BoundingBox, Circle, Point, ShapeOperation, SpatialIndex};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// Shapes scattered over a 1000 x 1000 square. A tiny linear congruential
+// generator keeps the layout the same from run to run without pulling
+// in rand.
+fn scattered(count: usize) -> Vec<Circle> {
+    let mut state: u64 = 42;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    (0..count)
+        .map(|_| Circle {
+            center: Point::new(next() * 1000.0, next() * 1000.0),
+            radius: 1.0 + next() * 4.0,
+        })
+        .collect()
+}
+
+fn range_query(c: &mut Criterion) {
+    let region = BoundingBox::new(Point::new(400.0, 400.0), Point::new(450.0, 450.0));
+    let mut group = c.benchmark_group("range query");
+    for count in [1_000, 10_000, 100_000] {
+        let shapes = scattered(count);
+        let index: SpatialIndex<Circle> = shapes.iter().cloned().collect();
+        group.bench_with_input(BenchmarkId::new("spatial index", count), &count, |b, _| {
+            b.iter(|| index.query(black_box(&region)).len())
+        });
+        group.bench_with_input(BenchmarkId::new("linear scan", count), &count, |b, _| {
+            b.iter(|| {
+                shapes
+                    .iter()
+                    .filter(|shape| shape.bounding_box().intersects(black_box(&region)))
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn nearest(c: &mut Criterion) {
+    let point = Point::new(512.0, 256.0);
+    let mut group = c.benchmark_group("nearest");
+    for count in [1_000, 10_000, 100_000] {
+        let shapes = scattered(count);
+        let index: SpatialIndex<Circle> = shapes.iter().cloned().collect();
+        group.bench_with_input(BenchmarkId::new("spatial index", count), &count, |b, _| {
+            b.iter(|| index.nearest(black_box(&point)).map(|(id, _)| id))
+        });
+        group.bench_with_input(BenchmarkId::new("linear scan", count), &count, |b, _| {
+            b.iter(|| {
+                shapes
+                    .iter()
+                    .map(|shape| shape.distance_to(black_box(&point)))
+                    .fold(f64::INFINITY, f64::min)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn build(c: &mut Criterion) {
+    let shapes = scattered(10_000);
+    c.bench_function("build index of 10000", |b| {
+        b.iter(|| {
+            shapes
+                .iter()
+                .cloned()
+                .collect::<SpatialIndex<Circle>>()
+                .len()
+        })
+    });
+}
+
+criterion_group!(benches, range_query, nearest, build);
+criterion_main!(benches);
@@ -1,4 +1,7 @@
-use cool_lib::{get_shape_area, NewsArticle, Rectangle, ShapeOperation, Summary, Triangle, Tweet};
+use cool_lib::{
+    get_shape_area, json, Circle, Format, NewsArticle, Point, Polygon, Rectangle, Shape,
+    ShapeOperation, Summary, Triangle, Tweet,
+};
 
 pub fn main() {
     let article = NewsArticle {
@@ -8,13 +11,10 @@ pub fn main() {
         location: String::from("Farhan Ghazi"),
     };
     println!("Article summary: {}", article.summarize());
-    let tweet = Tweet {
-        username: String::from("@farhanghazi97"),
-        content: String::from("Rust is awesome!"),
-        reply: false,
-        retweet: false,
-    };
+    let tweet = Tweet::new(1, "@farhanghazi97", "Rust is awesome!");
     println!("Article summary: {}", tweet.summarize());
+    println!("Short version: {}", tweet.summarize_truncated(20));
+    println!("As Markdown:\n{}", article.render(&Format::Markdown));
     let rectangle = Rectangle {
         width: 10.0,
         height: 2.0,
@@ -35,4 +35,32 @@ pub fn main() {
         get_shape_area(&rectangle)
     );
     println!("The area of this shape is: {:?}", get_shape_area(&triangle));
+    let circle = Circle {
+        center: Point::new(0.0, 0.0),
+        radius: 1.5,
+    };
+    println!(
+        "{} has perimeter {:.2} and centroid {:?}",
+        circle.dimensions(),
+        circle.perimeter(),
+        circle.centroid()
+    );
+    let polygon = Polygon::new(vec![
+        Point::new(0.0, 0.0),
+        Point::new(4.0, 0.0),
+        Point::new(4.0, 3.0),
+    ]);
+    println!(
+        "{} has area {} and bounding box {:?}",
+        polygon.dimensions(),
+        polygon.area(),
+        polygon.bounding_box()
+    );
+    let shapes = vec![
+        Shape::from(rectangle),
+        Shape::from(triangle),
+        Shape::from(circle),
+        Shape::from(polygon),
+    ];
+    println!("As JSON:\n{}", json::to_json(&shapes));
 }
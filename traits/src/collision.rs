@@ -0,0 +1,570 @@
+// Collision and containment checks between any two shapes.
+//
+// Each shape describes itself as an Outline: a polygon, a circle or an
+// ellipse. Overlap is decided with the separating axis theorem - two
+// convex shapes are apart exactly when there's a line we can project
+// both onto where their shadows don't meet. Only convex shapes play by
+// that rule, so concave polygons get cut into triangles first.
+//
+// Ellipses are squashed into a unit circle, dragging the other shape
+// along with them; stretching the whole plane doesn't change whether two
+// things overlap. Touching counts as overlapping and a shape sitting on
+// the edge still counts as inside.
+
+use crate::Point;
+
+// Slack for floating point rounding
+const EPSILON: f64 = 1e-9;
+
+// How many points stand in for a curved edge when we can't do better
+const CURVE_POINTS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    // Vertices in order, either way round. Can be concave but shouldn't
+    // cross itself.
+    Polygon(Vec<Point>),
+    Circle {
+        center: Point,
+        radius: f64,
+    },
+    // Turned `angle` radians anticlockwise, like the Ellipse shape
+    Ellipse {
+        center: Point,
+        radius_x: f64,
+        radius_y: f64,
+        angle: f64,
+    },
+}
+
+impl Outline {
+    pub fn contains_point(&self, point: &Point) -> bool {
+        match self {
+            Outline::Polygon(vertices) => polygon_contains(vertices, point),
+            Outline::Circle { center, radius } => center.distance(point) <= radius + EPSILON,
+            Outline::Ellipse { .. } => match self.unit_circle_view() {
+                Some(to_unit) => {
+                    let point = to_unit(point);
+                    point.x * point.x + point.y * point.y <= 1.0 + EPSILON
+                }
+                None => polygon_contains(&self.boundary(), point),
+            },
+        }
+    }
+
+    // A round shape facing an ellipse is traced from the outside, same as
+    // in contains, so one that only grazes the real curve still counts
+    pub fn intersects(&self, other: &Outline) -> bool {
+        if let Some((circle, other)) = self.squash(&other.covering()) {
+            return circle.intersects(&other);
+        }
+        if let Some((circle, this)) = other.squash(&self.covering()) {
+            return this.intersects(&circle);
+        }
+        let (ours, theirs) = (self.pieces(), other.pieces());
+        ours.iter()
+            .any(|a| theirs.iter().any(|b| pieces_overlap(a, b)))
+    }
+
+    // How far the point is from the outline, 0 if it's inside. Ellipses
+    // are measured against their traced edge, so that one's close but
+    // not exact.
+    pub fn distance_to(&self, point: &Point) -> f64 {
+        if self.contains_point(point) {
+            return 0.0;
+        }
+        match self {
+            Outline::Circle { center, radius } => center.distance(point) - radius,
+            _ => edges(&self.boundary())
+                .map(|(a, b)| distance_to_segment(point, a, b))
+                .fold(f64::INFINITY, f64::min),
+        }
+    }
+
+    // Whether `other` fits entirely inside this outline. Wherever a
+    // round `other` has to be traced, it's traced from the outside so
+    // the curve can't bulge out between the points.
+    pub fn contains(&self, other: &Outline) -> bool {
+        if let Some((circle, other)) = self.squash(&other.covering()) {
+            return circle.contains(&other);
+        }
+        match (self, other) {
+            (
+                Outline::Circle { center, radius },
+                Outline::Circle {
+                    center: inner,
+                    radius: inner_radius,
+                },
+            ) => center.distance(inner) + inner_radius <= radius + EPSILON,
+            // A circle has no dents, so whatever's inside it is inside
+            // as soon as its boundary is
+            (Outline::Circle { .. }, _) => other
+                .covering()
+                .iter()
+                .all(|point| self.contains_point(point)),
+            (Outline::Polygon(vertices), Outline::Circle { center, radius }) => {
+                polygon_contains(vertices, center)
+                    && edges(vertices)
+                        .all(|(a, b)| distance_to_segment(center, a, b) >= radius - EPSILON)
+            }
+            (Outline::Polygon(vertices), _) => {
+                // All the corners inside isn't enough for a concave
+                // polygon - an edge could still cut across a dent
+                let inner = other.covering();
+                inner.iter().all(|point| polygon_contains(vertices, point))
+                    && edges(&inner).all(|(a, b)| {
+                        let middle = Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+                        polygon_contains(vertices, &middle)
+                            && edges(vertices).all(|(c, d)| !segments_cross(a, b, c, d))
+                    })
+            }
+            // Proper ellipses were squashed above, so this one is flat
+            (Outline::Ellipse { .. }, _) => Outline::Polygon(self.boundary()).contains(other),
+        }
+    }
+
+    // For a proper ellipse, the stretch (well, a shift, a turn and a
+    // stretch) that takes it to the unit circle around the origin
+    fn unit_circle_view(&self) -> Option<impl Fn(&Point) -> Point> {
+        match *self {
+            Outline::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+                angle,
+            } if radius_x > 0.0 && radius_y > 0.0 => {
+                let (sin, cos) = angle.sin_cos();
+                Some(move |point: &Point| {
+                    let (dx, dy) = (point.x - center.x, point.y - center.y);
+                    Point::new(
+                        (dx * cos + dy * sin) / radius_x,
+                        (dy * cos - dx * sin) / radius_y,
+                    )
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // Points going round the edge. Exact for polygons; circles and
+    // ellipses are traced with CURVE_POINTS points on the curve, which
+    // cuts a little off between each pair.
+    fn boundary(&self) -> Vec<Point> {
+        self.traced(1.0)
+    }
+
+    // Like boundary, but a curve is traced just outside itself: pushed
+    // out by 1/cos(half a step), each edge only touches the curve in its
+    // middle, so the curve is entirely inside the points.
+    fn covering(&self) -> Vec<Point> {
+        let half_step = std::f64::consts::PI / CURVE_POINTS as f64;
+        self.traced(1.0 / half_step.cos())
+    }
+
+    // Curves are traced `scale` times their real size
+    fn traced(&self, scale: f64) -> Vec<Point> {
+        let curve = |center: &Point, radius_x: f64, radius_y: f64, turn: f64| {
+            let (sin, cos) = turn.sin_cos();
+            (0..CURVE_POINTS)
+                .map(|i| {
+                    let step = std::f64::consts::TAU * i as f64 / CURVE_POINTS as f64;
+                    let (x, y) = (scale * radius_x * step.cos(), scale * radius_y * step.sin());
+                    Point::new(center.x + x * cos - y * sin, center.y + x * sin + y * cos)
+                })
+                .collect()
+        };
+        match self {
+            Outline::Polygon(vertices) => vertices.clone(),
+            Outline::Circle { center, radius } => curve(center, *radius, *radius, 0.0),
+            Outline::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+                angle,
+            } => curve(center, *radius_x, *radius_y, *angle),
+        }
+    }
+
+    // If this is a proper ellipse, stretch the plane so it becomes the
+    // unit circle and hand back that circle along with `other` stretched
+    // the same way. Stretching a polygon keeps it a polygon, so `other`
+    // comes as its traced points - anything round has to be a polygon
+    // first so we don't go round in circles (literally).
+    fn squash(&self, other: &[Point]) -> Option<(Outline, Outline)> {
+        let to_unit = self.unit_circle_view()?;
+        let squashed = other.iter().map(to_unit).collect();
+        let circle = Outline::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        Some((circle, Outline::Polygon(squashed)))
+    }
+
+    fn pieces(&self) -> Vec<Piece> {
+        match self {
+            Outline::Polygon(vertices) if is_convex(vertices) => {
+                vec![Piece::Convex(vertices.clone())]
+            }
+            Outline::Polygon(vertices) => triangulate(vertices)
+                .into_iter()
+                .map(Piece::Convex)
+                .collect(),
+            Outline::Circle { center, radius } => vec![Piece::Circle(*center, *radius)],
+            // Only flat ellipses make it this far
+            Outline::Ellipse { .. } => vec![Piece::Convex(self.boundary())],
+        }
+    }
+}
+
+// A convex bit of an outline - something the separating axis theorem
+// can deal with directly
+enum Piece {
+    Convex(Vec<Point>),
+    Circle(Point, f64),
+}
+
+impl Piece {
+    // The directions worth checking for a gap. For a polygon that's the
+    // normal of every edge. A circle has infinitely many, but the only
+    // one that can matter points from its center at the nearest corner
+    // of the other shape.
+    fn axes(&self, other: &Piece) -> Vec<Point> {
+        match (self, other) {
+            (Piece::Convex(vertices), _) => edges(vertices)
+                .map(|(a, b)| Point::new(a.y - b.y, b.x - a.x))
+                .collect(),
+            (Piece::Circle(center, _), Piece::Convex(vertices)) => vertices
+                .iter()
+                .min_by(|a, b| center.distance(a).total_cmp(&center.distance(b)))
+                .map(|nearest| Point::new(nearest.x - center.x, nearest.y - center.y))
+                .into_iter()
+                .collect(),
+            (Piece::Circle(center, _), Piece::Circle(other, _)) => {
+                vec![Point::new(other.x - center.x, other.y - center.y)]
+            }
+        }
+    }
+
+    // The shadow this piece casts on a (unit length) axis
+    fn project(&self, axis: &Point) -> (f64, f64) {
+        let dot = |point: &Point| point.x * axis.x + point.y * axis.y;
+        match self {
+            Piece::Convex(vertices) => vertices
+                .iter()
+                .map(dot)
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), d| {
+                    (low.min(d), high.max(d))
+                }),
+            Piece::Circle(center, radius) => (dot(center) - radius, dot(center) + radius),
+        }
+    }
+}
+
+fn pieces_overlap(a: &Piece, b: &Piece) -> bool {
+    // The x and y axes are thrown in too - they never hurt, and they
+    // keep single points and flat slivers honest
+    let mut axes = vec![Point::new(1.0, 0.0), Point::new(0.0, 1.0)];
+    axes.extend(a.axes(b));
+    axes.extend(b.axes(a));
+    axes.iter().all(|axis| {
+        let length = axis.x.hypot(axis.y);
+        if length <= EPSILON {
+            return true;
+        }
+        let axis = Point::new(axis.x / length, axis.y / length);
+        let (a_low, a_high) = a.project(&axis);
+        let (b_low, b_high) = b.project(&axis);
+        a_low <= b_high + EPSILON && b_low <= a_high + EPSILON
+    })
+}
+
+fn edges(vertices: &[Point]) -> impl Iterator<Item = (&Point, &Point)> {
+    vertices.iter().zip(vertices.iter().cycle().skip(1))
+}
+
+// Positive when a -> b -> c turns left, negative when it turns right
+fn cross(a: &Point, b: &Point, c: &Point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn is_convex(vertices: &[Point]) -> bool {
+    let n = vertices.len();
+    let turns: Vec<f64> = (0..n)
+        .map(|i| cross(&vertices[i], &vertices[(i + 1) % n], &vertices[(i + 2) % n]))
+        .filter(|turn| turn.abs() > EPSILON)
+        .collect();
+    turns.iter().all(|turn| *turn > 0.0) || turns.iter().all(|turn| *turn < 0.0)
+}
+
+fn distance_to_segment(point: &Point, a: &Point, b: &Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return point.distance(a);
+    }
+    let t = (((point.x - a.x) * dx + (point.y - a.y) * dy) / length).clamp(0.0, 1.0);
+    point.distance(&Point::new(a.x + t * dx, a.y + t * dy))
+}
+
+// Whether two segments cross at a single point in the middle of both.
+// Touching at an end or running along each other doesn't count.
+fn segments_cross(a: &Point, b: &Point, c: &Point, d: &Point) -> bool {
+    let (d1, d2) = (cross(a, b, c), cross(a, b, d));
+    let (d3, d4) = (cross(c, d, a), cross(c, d, b));
+    ((d1 > EPSILON && d2 < -EPSILON) || (d1 < -EPSILON && d2 > EPSILON))
+        && ((d3 > EPSILON && d4 < -EPSILON) || (d3 < -EPSILON && d4 > EPSILON))
+}
+
+// Even-odd rule: a ray going right from an inside point crosses the
+// edge an odd number of times. Points on the edge itself are inside.
+fn polygon_contains(vertices: &[Point], point: &Point) -> bool {
+    if edges(vertices).any(|(a, b)| distance_to_segment(point, a, b) <= EPSILON) {
+        return true;
+    }
+    edges(vertices)
+        .filter(|(a, b)| (a.y > point.y) != (b.y > point.y))
+        .filter(|(a, b)| point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y))
+        .count()
+        % 2
+        == 1
+}
+
+// Cut a simple polygon into triangles by repeatedly clipping off an
+// "ear": a corner whose triangle has no other vertex inside it
+fn triangulate(vertices: &[Point]) -> Vec<Vec<Point>> {
+    let mut remaining = vertices.to_vec();
+    // Work counter-clockwise so an ear is always a left turn
+    if edges(&remaining)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f64>()
+        < 0.0
+    {
+        remaining.reverse();
+    }
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (
+                &remaining[(i + n - 1) % n],
+                &remaining[i],
+                &remaining[(i + 1) % n],
+            );
+            cross(a, b, c) > EPSILON
+                && remaining
+                    .iter()
+                    .filter(|p| ![a, b, c].contains(p))
+                    .all(|p| !polygon_contains(&[*a, *b, *c], p))
+        });
+        // Only a degenerate polygon has no ears; keep what's left whole
+        let Some(i) = ear else { break };
+        triangles.push(vec![
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push(remaining);
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Circle, Ellipse, Polygon, Rectangle, ShapeOperation, Triangle};
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon {
+        Polygon::new(vec![
+            Point::new(x, y),
+            Point::new(x + size, y),
+            Point::new(x + size, y + size),
+            Point::new(x, y + size),
+        ])
+    }
+
+    // An L shape whose dent is the square from (1, 1) to (2, 2)
+    fn l_shape() -> Polygon {
+        Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(2.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 2.0),
+            Point::new(0.0, 2.0),
+        ])
+    }
+
+    #[test]
+    fn rectangle_and_triangle() {
+        let rectangle = Rectangle {
+            width: 4.0,
+            height: 3.0,
+        };
+        let triangle = Triangle {
+            base: 6.0,
+            height: 4.0,
+        };
+        // Both sit on the origin
+        assert!(rectangle.intersects(&triangle));
+        assert!(triangle.intersects(&rectangle));
+        // The rectangle's top-left corner pokes out of the triangle
+        assert!(!triangle.contains_shape(&rectangle));
+        assert!(!rectangle.contains_point(&Point::new(4.5, 1.0)));
+        assert!(triangle.contains_point(&Point::new(4.5, 1.0)));
+
+        let small = Triangle {
+            base: 2.0,
+            height: 2.0,
+        };
+        assert!(rectangle.contains_shape(&small));
+        assert!(!small.contains_shape(&rectangle));
+    }
+
+    #[test]
+    fn separated_by_a_slanted_edge() {
+        // Bounding boxes overlap, but the triangle's slope keeps the
+        // square clear - only the slanted axis can tell
+        let triangle = Triangle {
+            base: 4.0,
+            height: 4.0,
+        };
+        let square = square(3.5, 3.0, 1.0);
+        assert!(triangle.bounding_box().max.x > square.bounding_box().min.x);
+        assert!(!triangle.intersects(&square));
+    }
+
+    #[test]
+    fn circle_and_polygon() {
+        let circle = Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        // Near the corner: the boxes overlap but the circle misses it
+        let corner = square(0.8, 0.8, 1.0);
+        assert!(!circle.intersects(&corner));
+        assert!(!corner.intersects(&circle));
+        assert!(circle.intersects(&square(0.5, 0.5, 1.0)));
+        // Just touching counts
+        assert!(circle.intersects(&square(1.0, -0.5, 1.0)));
+
+        assert!(square(-2.0, -2.0, 4.0).contains_shape(&circle));
+        assert!(!square(-1.0, -1.0, 1.9).contains_shape(&circle));
+        assert!(circle.contains_shape(&square(-0.5, -0.5, 1.0)));
+        assert!(!circle.contains_shape(&square(-0.8, -0.8, 1.6)));
+    }
+
+    #[test]
+    fn circles_and_ellipses() {
+        let circle = |x, radius| Circle {
+            center: Point::new(x, 0.0),
+            radius,
+        };
+        assert!(circle(0.0, 1.0).intersects(&circle(2.0, 1.0)));
+        assert!(!circle(0.0, 1.0).intersects(&circle(2.1, 1.0)));
+        assert!(circle(0.0, 3.0).contains_shape(&circle(1.0, 2.0)));
+        assert!(!circle(0.0, 3.0).contains_shape(&circle(1.5, 2.0)));
+
+        let ellipse = Ellipse {
+            center: Point::new(0.0, 0.0),
+            radius_x: 4.0,
+            radius_y: 1.0,
+            angle: 0.0,
+        };
+        assert!(ellipse.contains_point(&Point::new(3.9, 0.0)));
+        assert!(!ellipse.contains_point(&Point::new(3.0, 0.9)));
+        assert!(ellipse.intersects(&circle(4.5, 1.0)));
+        assert!(!ellipse.intersects(&square(3.0, 0.8, 1.0)));
+        assert!(ellipse.contains_shape(&square(-0.5, -0.5, 1.0)));
+        assert!(!ellipse.contains_shape(&circle(0.0, 2.0)));
+        assert!(circle(0.0, 5.0).contains_shape(&ellipse));
+
+        // Turned half a tracing step, every traced point of this circle
+        // is inside the square but the circle itself pokes out of it
+        let bulging = Ellipse {
+            center: Point::new(1.0, 1.0),
+            radius_x: 1.0001,
+            radius_y: 1.0001,
+            angle: std::f64::consts::PI / CURVE_POINTS as f64,
+        };
+        assert!(!square(0.0, 0.0, 2.0).contains_shape(&bulging));
+
+        // This one sits on top of the long ellipse and touches it right
+        // between two of its own traced points
+        let resting = |y| Ellipse {
+            center: Point::new(0.0, y),
+            radius_x: 1.0,
+            radius_y: 1.0,
+            angle: std::f64::consts::PI / CURVE_POINTS as f64,
+        };
+        assert!(ellipse.intersects(&resting(2.0)));
+        assert!(resting(2.0).intersects(&ellipse));
+        assert!(!ellipse.intersects(&resting(2.01)));
+    }
+
+    #[test]
+    fn concave_polygons() {
+        let l = l_shape();
+        assert!(l.contains_point(&Point::new(0.5, 1.5)));
+        assert!(!l.contains_point(&Point::new(1.5, 1.5)));
+        // On the edge
+        assert!(l.contains_point(&Point::new(1.0, 1.5)));
+
+        // Sitting in the dent
+        let dent = square(1.2, 1.2, 0.5);
+        assert!(!l.intersects(&dent));
+        assert!(l.intersects(&square(0.9, 0.9, 0.5)));
+
+        // Both ends of this bar are inside the L but it cuts the dent
+        let bar = Polygon::new(vec![
+            Point::new(0.5, 1.8),
+            Point::new(1.8, 0.5),
+            Point::new(1.8, 0.4),
+            Point::new(0.4, 1.8),
+        ]);
+        assert!(bar.vertices.iter().all(|v| l.contains_point(v)));
+        assert!(!l.contains_shape(&bar));
+        assert!(l.contains_shape(&square(0.1, 0.1, 0.8)));
+    }
+
+    #[test]
+    fn containment_generalises_can_hold() {
+        // The old Rectangle::can_hold, now for any pair of shapes
+        let larger = Rectangle {
+            width: 8.0,
+            height: 7.0,
+        };
+        let smaller = Rectangle {
+            width: 5.0,
+            height: 1.0,
+        };
+        assert!(larger.contains_shape(&smaller));
+        assert!(!smaller.contains_shape(&larger));
+        assert!(larger.contains_shape(&larger));
+    }
+
+    #[test]
+    fn distance_from_outside() {
+        let circle = Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        assert_eq!(0.0, circle.outline().distance_to(&Point::new(0.5, 0.0)));
+        assert_eq!(2.0, circle.outline().distance_to(&Point::new(0.0, 3.0)));
+        let l = l_shape().outline();
+        assert_eq!(0.0, l.distance_to(&Point::new(0.5, 0.5)));
+        // From inside the dent to its nearest wall
+        assert!((l.distance_to(&Point::new(1.5, 1.2)) - 0.2).abs() < EPSILON);
+    }
+
+    #[test]
+    fn triangulation_covers_the_polygon() {
+        let triangles = triangulate(&l_shape().vertices);
+        assert_eq!(4, triangles.len());
+        let area: f64 = triangles
+            .into_iter()
+            .map(|triangle| Polygon::new(triangle).area())
+            .sum();
+        assert!((area - 3.0).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// What ShapeOperation::dimensions() hands back. Callers can match on it,
+// compare it or serialise it, and Display still gives the old
+// "Shape | (...)" text for humans.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Dimensions {
+    Rectangle {
+        width: f64,
+        height: f64,
+    },
+    Triangle {
+        base: f64,
+        height: f64,
+    },
+    Circle {
+        radius: f64,
+    },
+    // Turned `angle` radians anticlockwise, same as the shape. Missing
+    // from older JSON, which means it isn't turned.
+    Ellipse {
+        radius_x: f64,
+        radius_y: f64,
+        #[serde(default)]
+        angle: f64,
+    },
+    Polygon {
+        vertices: usize,
+        width: f64,
+        height: f64,
+    },
+    // Just the size of the bounding box, for shapes that don't say more
+    Bounds {
+        width: f64,
+        height: f64,
+    },
+}
+
+impl fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dimensions::Rectangle { width, height } => {
+                write!(f, "Rectangle | (Width: {}, Height: {})", width, height)
+            }
+            Dimensions::Triangle { base, height } => {
+                write!(f, "Triangle | (Base: {}, Height: {})", base, height)
+            }
+            Dimensions::Circle { radius } => write!(f, "Circle | (Radius: {})", radius),
+            Dimensions::Ellipse {
+                radius_x,
+                radius_y,
+                angle,
+            } => {
+                write!(
+                    f,
+                    "Ellipse | (Radius X: {}, Radius Y: {}",
+                    radius_x, radius_y
+                )?;
+                if *angle != 0.0 {
+                    write!(f, ", Angle: {}", angle)?;
+                }
+                write!(f, ")")
+            }
+            Dimensions::Polygon {
+                vertices,
+                width,
+                height,
+            } => write!(
+                f,
+                "Polygon | (Vertices: {}, Width: {}, Height: {})",
+                vertices, width, height
+            ),
+            Dimensions::Bounds { width, height } => {
+                write!(f, "Shape | (Width: {}, Height: {})", width, height)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_reads_like_before() {
+        let triangle = Dimensions::Triangle {
+            base: 6.0,
+            height: 4.0,
+        };
+        assert_eq!("Triangle | (Base: 6, Height: 4)", triangle.to_string());
+        assert_eq!(
+            "Circle | (Radius: 1.5)",
+            Dimensions::Circle { radius: 1.5 }.to_string()
+        );
+    }
+
+    #[test]
+    fn serialises_with_a_shape_tag() {
+        let ellipse = Dimensions::Ellipse {
+            radius_x: 3.0,
+            radius_y: 2.0,
+            angle: 0.5,
+        };
+        let json = serde_json::to_string(&ellipse).unwrap();
+        assert_eq!(
+            r#"{"shape":"ellipse","radius_x":3.0,"radius_y":2.0,"angle":0.5}"#,
+            json
+        );
+        assert_eq!(ellipse, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn ellipses_mention_their_angle_once_turned() {
+        let ellipse = |angle| Dimensions::Ellipse {
+            radius_x: 3.0,
+            radius_y: 2.0,
+            angle,
+        };
+        assert_eq!(
+            "Ellipse | (Radius X: 3, Radius Y: 2)",
+            ellipse(0.0).to_string()
+        );
+        assert_eq!(
+            "Ellipse | (Radius X: 3, Radius Y: 2, Angle: 0.5)",
+            ellipse(0.5).to_string()
+        );
+        let old = r#"{"shape":"ellipse","radius_x":3.0,"radius_y":2.0}"#;
+        assert_eq!(ellipse(0.0), serde_json::from_str(old).unwrap());
+    }
+}
@@ -0,0 +1,552 @@
+// A news feed: articles, tweets and anything else that implements
+// Summary, each stamped with when it was published and kept newest
+// first. Feeds can be merged, filtered and split into pages, and
+// articles can be read in from RSS and Atom files.
+
+use crate::{NewsArticle, Summary};
+use chrono::{DateTime, Utc};
+use std::{error::Error, fmt, fs, io, path::Path};
+
+pub struct FeedItem {
+    pub published: DateTime<Utc>,
+    pub item: Box<dyn Summary>,
+}
+
+impl fmt::Debug for FeedItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FeedItem")
+            .field("published", &self.published)
+            .field("summary", &self.item.summarize())
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Feed {
+    // Newest first
+    items: Vec<FeedItem>,
+}
+
+impl Feed {
+    pub fn new() -> Feed {
+        Feed::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Slots the item in by date. Items published at the same moment stay
+    // in the order they were added.
+    pub fn push(&mut self, published: DateTime<Utc>, item: impl Summary + 'static) {
+        self.push_boxed(published, Box::new(item));
+    }
+
+    pub fn push_boxed(&mut self, published: DateTime<Utc>, item: Box<dyn Summary>) {
+        let at = self
+            .items
+            .partition_point(|existing| existing.published >= published);
+        self.items.insert(at, FeedItem { published, item });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FeedItem> {
+        self.items.iter()
+    }
+
+    // One feed out of many, still newest first. Each feed is already in
+    // order, so this is the merge step of merge sort: keep taking the
+    // newest of the items at the front of each feed.
+    pub fn merge(feeds: impl IntoIterator<Item = Feed>) -> Feed {
+        let mut fronts: Vec<_> = feeds
+            .into_iter()
+            .map(|feed| feed.items.into_iter().peekable())
+            .collect();
+        let mut items = Vec::with_capacity(fronts.iter().map(|front| front.len()).sum());
+        loop {
+            // On a tie the earlier feed goes first
+            let newest = fronts
+                .iter_mut()
+                .enumerate()
+                .filter_map(|(i, front)| front.peek().map(|item| (i, item.published)))
+                .reduce(|best, next| if next.1 > best.1 { next } else { best });
+            match newest {
+                Some((i, _)) => items.extend(fronts[i].next()),
+                None => break,
+            }
+        }
+        Feed { items }
+    }
+
+    // Just the matching items, for filtering and then paging
+    pub fn filtered(self, filter: &Filter) -> Feed {
+        Feed {
+            items: self
+                .items
+                .into_iter()
+                .filter(|item| filter.matches(item.item.as_ref()))
+                .collect(),
+        }
+    }
+
+    // Pages are numbered from 1, and asking for page 0 gets page 1.
+    // Past the end gives an empty page.
+    pub fn page(&self, number: usize, size: usize) -> Page<'_> {
+        let number = number.max(1);
+        let size = size.max(1);
+        let start = (number - 1).saturating_mul(size);
+        let items = self
+            .items
+            .get(start..)
+            .map(|rest| &rest[..rest.len().min(size)])
+            .unwrap_or(&[]);
+        Page {
+            items,
+            number,
+            total_pages: self.items.len().div_ceil(size),
+        }
+    }
+
+    // Every item in an RSS 2.0 or Atom document, as NewsArticles
+    pub fn from_xml(xml: &str) -> Result<Feed, FeedError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let root = document.root_element();
+        let mut feed = Feed::new();
+        let entries = match root.tag_name().name() {
+            "rss" => rss_entries(root)?,
+            "feed" => atom_entries(root)?,
+            other => return Err(FeedError::NotAFeed(other.to_string())),
+        };
+        for (published, article) in entries {
+            feed.push(published, article);
+        }
+        Ok(feed)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Feed, FeedError> {
+        Feed::from_xml(&fs::read_to_string(path)?)
+    }
+}
+
+pub struct Page<'a> {
+    pub items: &'a [FeedItem],
+    // Starting from 1
+    pub number: usize,
+    pub total_pages: usize,
+}
+
+impl Page<'_> {
+    pub fn has_next(&self) -> bool {
+        self.number < self.total_pages
+    }
+}
+
+// Which items to keep. Author and keyword both ignore case, and an
+// author matches with or without a leading @.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    author: Option<String>,
+    keyword: Option<String>,
+}
+
+impl Filter {
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    pub fn author(mut self, author: &str) -> Filter {
+        self.author = Some(normalise_author(author));
+        self
+    }
+
+    // Looked for in the title and the body
+    pub fn keyword(mut self, keyword: &str) -> Filter {
+        self.keyword = Some(keyword.to_lowercase());
+        self
+    }
+
+    pub fn matches(&self, item: &dyn Summary) -> bool {
+        let author_matches = self
+            .author
+            .as_ref()
+            .is_none_or(|author| *author == normalise_author(&item.summarize_author()));
+        let keyword_matches = self.keyword.as_ref().is_none_or(|keyword| {
+            let parts = item.parts();
+            [parts.title.unwrap_or_default(), parts.body]
+                .iter()
+                .any(|text| text.to_lowercase().contains(keyword))
+        });
+        author_matches && keyword_matches
+    }
+}
+
+fn normalise_author(author: &str) -> String {
+    author.trim().trim_start_matches('@').to_lowercase()
+}
+
+#[derive(Debug)]
+pub enum FeedError {
+    Io(io::Error),
+    Xml(roxmltree::Error),
+    // The root element, when it's neither <rss> nor <feed>
+    NotAFeed(String),
+    // An item with no date we can read, by its title
+    BadDate(String),
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeedError::Io(error) => write!(f, "could not read the feed: {}", error),
+            FeedError::Xml(error) => write!(f, "the feed is not valid XML: {}", error),
+            FeedError::NotAFeed(root) => {
+                write!(f, "expected an RSS or Atom feed, found <{}>", root)
+            }
+            FeedError::BadDate(title) => write!(f, "'{}' has no readable date", title),
+        }
+    }
+}
+
+impl Error for FeedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FeedError::Io(error) => Some(error),
+            FeedError::Xml(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FeedError {
+    fn from(error: io::Error) -> FeedError {
+        FeedError::Io(error)
+    }
+}
+
+impl From<roxmltree::Error> for FeedError {
+    fn from(error: roxmltree::Error) -> FeedError {
+        FeedError::Xml(error)
+    }
+}
+
+type Node<'a, 'input> = roxmltree::Node<'a, 'input>;
+
+// The text of the first child element called `name`, ignoring
+// namespaces (so <dc:creator> is found as "creator")
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name)
+        .map(|found| {
+            found
+                .descendants()
+                .filter(|d| d.is_text())
+                .filter_map(|d| d.text())
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .filter(|text| !text.is_empty())
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.tag_name().name() == name)
+}
+
+// Descriptions are often HTML squeezed into a text node. A summary
+// wants the words, not the markup.
+fn strip_tags(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// RSS:
+//   <rss><channel><title>Source</title>
+//     <item><title/><author/> or <dc:creator/><pubDate/><description/></item>
+//   </channel></rss>
+// The channel's title stands in for the article's location.
+fn rss_entries(root: Node) -> Result<Vec<(DateTime<Utc>, NewsArticle)>, FeedError> {
+    let Some(channel) = child(root, "channel") else {
+        return Ok(Vec::new());
+    };
+    let source = child_text(channel, "title").unwrap_or_default();
+    channel
+        .children()
+        .filter(|node| node.tag_name().name() == "item")
+        .map(|item| {
+            let headline = child_text(item, "title").unwrap_or_default();
+            let published = child_text(item, "pubDate")
+                .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+                .ok_or_else(|| FeedError::BadDate(headline.clone()))?;
+            let author = child_text(item, "creator")
+                .or_else(|| child_text(item, "author"))
+                .unwrap_or_else(|| source.clone());
+            let content = child_text(item, "encoded")
+                .or_else(|| child_text(item, "description"))
+                .unwrap_or_default();
+            Ok((
+                published.with_timezone(&Utc),
+                NewsArticle {
+                    headline,
+                    location: source.clone(),
+                    author,
+                    content: strip_tags(&content),
+                },
+            ))
+        })
+        .collect()
+}
+
+// Atom:
+//   <feed><title>Source</title><author><name/></author>
+//     <entry><title/><author><name/></author><published/> or <updated/>
+//       <summary/> or <content/></entry>
+//   </feed>
+fn atom_entries(root: Node) -> Result<Vec<(DateTime<Utc>, NewsArticle)>, FeedError> {
+    let source = child_text(root, "title").unwrap_or_default();
+    let feed_author = child(root, "author").and_then(|author| child_text(author, "name"));
+    root.children()
+        .filter(|node| node.tag_name().name() == "entry")
+        .map(|entry| {
+            let headline = child_text(entry, "title").unwrap_or_default();
+            let published = child_text(entry, "published")
+                .or_else(|| child_text(entry, "updated"))
+                .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                .ok_or_else(|| FeedError::BadDate(headline.clone()))?;
+            let author = child(entry, "author")
+                .and_then(|author| child_text(author, "name"))
+                .or_else(|| feed_author.clone())
+                .unwrap_or_else(|| source.clone());
+            let content = child_text(entry, "summary")
+                .or_else(|| child_text(entry, "content"))
+                .unwrap_or_default();
+            Ok((
+                published.with_timezone(&Utc),
+                NewsArticle {
+                    headline,
+                    location: source.clone(),
+                    author,
+                    content: strip_tags(&content),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tweet;
+    use chrono::TimeZone;
+    use std::env;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn tweet(username: &str, content: &str) -> Tweet {
+        Tweet::new(0, username, content)
+    }
+
+    fn article(headline: &str, author: &str) -> NewsArticle {
+        NewsArticle {
+            headline: headline.to_string(),
+            location: String::from("Sydney"),
+            author: author.to_string(),
+            content: String::from("Read all about it"),
+        }
+    }
+
+    fn summaries(feed: &Feed) -> Vec<String> {
+        feed.iter().map(|item| item.item.summarize()).collect()
+    }
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Harbour Times</title>
+    <item>
+      <title>Ferry runs late</title>
+      <dc:creator>Sam Jones</dc:creator>
+      <pubDate>Tue, 05 Mar 2024 09:30:00 +1100</pubDate>
+      <description>&lt;p&gt;The 9:05 ferry was &lt;b&gt;late&lt;/b&gt; again.&lt;/p&gt;</description>
+    </item>
+    <item>
+      <title>Bridge climb reopens</title>
+      <pubDate>Wed, 06 Mar 2024 08:00:00 GMT</pubDate>
+      <description>Good news for climbers.</description>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Rust Blog</title>
+  <author><name>The Rust Team</name></author>
+  <entry>
+    <title>Rust 1.0</title>
+    <published>2015-05-15T00:00:00Z</published>
+    <summary>Stable at last.</summary>
+  </entry>
+  <entry>
+    <title>Traits, explained</title>
+    <author><name>Ferris</name></author>
+    <updated>2024-03-04T12:00:00+00:00</updated>
+    <content type="html">&lt;p&gt;Shared behaviour&lt;/p&gt;</content>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn push_keeps_newest_first() {
+        let mut feed = Feed::new();
+        feed.push(at(2, 0), tweet("@b", "middle"));
+        feed.push(at(3, 0), tweet("@a", "newest"));
+        feed.push(at(1, 0), article("Oldest", "C"));
+        feed.push(at(2, 0), tweet("@b", "middle, added later"));
+        assert_eq!(
+            vec![
+                "@a: newest",
+                "@b: middle",
+                "@b: middle, added later",
+                "Oldest, by C, (Sydney)"
+            ],
+            summaries(&feed)
+        );
+    }
+
+    #[test]
+    fn merging_interleaves_by_date() {
+        let mut tweets = Feed::new();
+        tweets.push(at(1, 10), tweet("@a", "one"));
+        tweets.push(at(3, 10), tweet("@a", "three"));
+        let mut articles = Feed::new();
+        articles.push(at(2, 10), article("Two", "B"));
+        articles.push(at(4, 10), article("Four", "B"));
+        articles.push(at(3, 10), article("Three too", "B"));
+
+        let merged = Feed::merge(vec![tweets, articles, Feed::new()]);
+        assert_eq!(
+            vec![
+                "Four, by B, (Sydney)",
+                "@a: three",
+                "Three too, by B, (Sydney)",
+                "Two, by B, (Sydney)",
+                "@a: one"
+            ],
+            summaries(&merged)
+        );
+    }
+
+    #[test]
+    fn filter_by_author_and_keyword() {
+        let feed = || {
+            let mut feed = Feed::new();
+            feed.push(at(1, 0), tweet("@Ferris", "Rust is great"));
+            feed.push(at(2, 0), tweet("@corro", "Unsafe Rust"));
+            feed.push(at(3, 0), article("Crabs on the beach", "ferris"));
+            feed
+        };
+        assert_eq!(2, feed().filtered(&Filter::new().author("ferris")).len());
+        assert_eq!(2, feed().filtered(&Filter::new().keyword("RUST")).len());
+        let rusty_ferris = Filter::new().author("@FERRIS").keyword("rust");
+        assert_eq!(
+            vec!["@Ferris: Rust is great"],
+            summaries(&feed().filtered(&rusty_ferris))
+        );
+    }
+
+    #[test]
+    fn pages() {
+        let mut feed = Feed::new();
+        for hour in 0..7 {
+            feed.push(at(1, hour), tweet("@a", &hour.to_string()));
+        }
+        let first = feed.page(1, 3);
+        assert_eq!(3, first.total_pages);
+        assert!(first.has_next());
+        assert_eq!("@a: 6", first.items[0].item.summarize());
+
+        let last = feed.page(3, 3);
+        assert_eq!(1, last.items.len());
+        assert_eq!("@a: 0", last.items[0].item.summarize());
+        assert!(!last.has_next());
+
+        assert!(feed.page(4, 3).items.is_empty());
+        let zero = feed.page(0, 3);
+        assert_eq!(1, zero.number);
+        assert_eq!("@a: 6", zero.items[0].item.summarize());
+        assert!(zero.has_next());
+    }
+
+    #[test]
+    fn reads_rss() {
+        let feed = Feed::from_xml(RSS).unwrap();
+        assert_eq!(
+            vec![
+                "Bridge climb reopens, by Harbour Times, (Harbour Times)",
+                "Ferry runs late, by Sam Jones, (Harbour Times)"
+            ],
+            summaries(&feed)
+        );
+        let ferry = &feed.page(2, 1).items[0];
+        assert_eq!(
+            Utc.with_ymd_and_hms(2024, 3, 4, 22, 30, 0).unwrap(),
+            ferry.published
+        );
+        assert_eq!("The 9:05 ferry was late again.", ferry.item.parts().body);
+    }
+
+    #[test]
+    fn reads_atom() {
+        let feed = Feed::from_xml(ATOM).unwrap();
+        assert_eq!(
+            vec![
+                "Traits, explained, by Ferris, (Rust Blog)",
+                "Rust 1.0, by The Rust Team, (Rust Blog)"
+            ],
+            summaries(&feed)
+        );
+        assert_eq!(
+            "Shared behaviour",
+            feed.iter().next().unwrap().item.parts().body
+        );
+    }
+
+    #[test]
+    fn loads_files_and_reports_problems() {
+        let path = env::temp_dir().join(format!("cool_lib_feed_{}.xml", std::process::id()));
+        fs::write(&path, ATOM).unwrap();
+        let imported = Feed::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Imported articles mix with everything else
+        let mut tweets = Feed::new();
+        tweets.push(at(5, 0), tweet("@ferris", "New post!"));
+        let merged = Feed::merge([imported, tweets]);
+        assert_eq!(3, merged.len());
+        assert_eq!(2, merged.filtered(&Filter::new().author("Ferris")).len());
+
+        assert!(matches!(Feed::load(&path), Err(FeedError::Io(_))));
+        assert!(matches!(Feed::from_xml("<rss>"), Err(FeedError::Xml(_))));
+        assert!(matches!(
+            Feed::from_xml("<html/>"),
+            Err(FeedError::NotAFeed(root)) if root == "html"
+        ));
+        let undated = "<rss><channel><item><title>When?</title></item></channel></rss>";
+        match Feed::from_xml(undated) {
+            Err(error) => assert_eq!("'When?' has no readable date", error.to_string()),
+            Ok(_) => panic!("an undated item should be rejected"),
+        }
+    }
+}
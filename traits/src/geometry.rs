@@ -0,0 +1,151 @@
+// The small bits of plane geometry the shapes are built from
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+// An axis-aligned box, given by its bottom-left and top-right corners
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    // The box around a bunch of points. Empty input gives a box with
+    // min at +infinity and max at -infinity, which contains nothing.
+    pub fn around<'a>(points: impl IntoIterator<Item = &'a Point>) -> BoundingBox {
+        let empty = BoundingBox::new(
+            Point::new(f64::INFINITY, f64::INFINITY),
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        );
+        points.into_iter().fold(empty, |bbox, point| {
+            BoundingBox::new(
+                Point::new(bbox.min.x.min(point.x), bbox.min.y.min(point.y)),
+                Point::new(bbox.max.x.max(point.x), bbox.max.y.max(point.y)),
+            )
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width().max(0.0) * self.height().max(0.0)
+    }
+
+    // The smallest box holding both. An empty box (see around) leaves
+    // the other one as it is.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        )
+    }
+
+    // Touching counts
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn contains(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.min.x
+            && self.min.y <= other.min.y
+            && other.max.x <= self.max.x
+            && other.max.y <= self.max.y
+    }
+
+    // How far the point is from the nearest bit of the box, 0 inside it
+    pub fn distance_to(&self, point: &Point) -> f64 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx.hypot(dy)
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_between_points() {
+        assert_eq!(5.0, Point::new(1.0, 1.0).distance(&Point::new(4.0, 5.0)));
+    }
+
+    #[test]
+    fn box_around_points() {
+        let points = [
+            Point::new(1.0, 5.0),
+            Point::new(-2.0, 3.0),
+            Point::new(4.0, -1.0),
+        ];
+        let bbox = BoundingBox::around(&points);
+        assert_eq!(Point::new(-2.0, -1.0), bbox.min);
+        assert_eq!(Point::new(4.0, 5.0), bbox.max);
+        assert_eq!(6.0, bbox.width());
+        assert_eq!(6.0, bbox.height());
+        assert_eq!(Point::new(1.0, 2.0), bbox.center());
+
+        let other = BoundingBox::new(Point::new(3.0, 3.0), Point::new(9.0, 4.0));
+        assert_eq!(
+            BoundingBox::new(Point::new(-2.0, -1.0), Point::new(9.0, 5.0)),
+            bbox.union(&other)
+        );
+        assert_eq!(other, BoundingBox::around(&[]).union(&other));
+    }
+
+    #[test]
+    fn box_overlap_and_distance() {
+        let bbox = BoundingBox::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+        assert_eq!(8.0, bbox.area());
+        assert_eq!(0.0, BoundingBox::around(&[]).area());
+        let touching = BoundingBox::new(Point::new(4.0, 1.0), Point::new(5.0, 3.0));
+        assert!(bbox.intersects(&touching));
+        assert!(!bbox.contains(&touching));
+        assert!(bbox.contains(&BoundingBox::new(
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 2.0)
+        )));
+        assert!(!bbox.intersects(&BoundingBox::new(
+            Point::new(4.5, 0.0),
+            Point::new(5.0, 1.0)
+        )));
+
+        assert_eq!(0.0, bbox.distance_to(&Point::new(1.0, 1.0)));
+        assert_eq!(3.0, bbox.distance_to(&Point::new(-3.0, 1.0)));
+        assert_eq!(5.0, bbox.distance_to(&Point::new(7.0, 6.0)));
+    }
+}
@@ -0,0 +1,102 @@
+// Saving and loading collections of shapes as JSON. Each shape is an
+// object with a "type" tag, e.g.
+//
+//   [{"type": "circle", "center": {"x": 0.0, "y": 0.0}, "radius": 1.0}]
+
+use crate::Shape;
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+pub fn to_json(shapes: &[Shape]) -> String {
+    // Serialising plain structs and enums like ours can't fail
+    serde_json::to_string_pretty(shapes).expect("shapes always serialise")
+}
+
+pub fn from_json(json: &str) -> Result<Vec<Shape>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+pub fn save(path: impl AsRef<Path>, shapes: &[Shape]) -> io::Result<()> {
+    fs::write(path, to_json(shapes))
+}
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Shape>> {
+    let contents = fs::read_to_string(path)?;
+    from_json(&contents).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Circle, Ellipse, Point, Polygon, Rectangle, Triangle};
+    use std::env;
+
+    fn all_shapes() -> Vec<Shape> {
+        vec![
+            Rectangle {
+                width: 4.0,
+                height: 3.0,
+            }
+            .into(),
+            Triangle {
+                base: 6.0,
+                height: 4.0,
+            }
+            .into(),
+            Circle {
+                center: Point::new(1.0, 2.0),
+                radius: 2.0,
+            }
+            .into(),
+            Ellipse {
+                center: Point::new(-1.0, 0.5),
+                radius_x: 3.0,
+                radius_y: 2.0,
+                angle: 0.0,
+            }
+            .into(),
+            Polygon::new(vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(0.0, 1.0),
+            ])
+            .into(),
+        ]
+    }
+
+    #[test]
+    fn shapes_carry_a_type_tag() {
+        let json = to_json(&all_shapes()[..1]);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!("rectangle", value[0]["type"]);
+        assert_eq!(4.0, value[0]["width"]);
+    }
+
+    #[test]
+    fn round_trip_through_a_file() {
+        let path = env::temp_dir().join(format!("cool_lib_shapes_{}.json", std::process::id()));
+        save(&path, &all_shapes()).unwrap();
+        assert_eq!(all_shapes(), load(&path).unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn ellipses_saved_before_they_could_turn_still_load() {
+        let shapes = from_json(
+            r#"[{"type": "ellipse", "center": {"x": 0.0, "y": 0.0}, "radius_x": 2.0, "radius_y": 1.0}]"#,
+        )
+        .unwrap();
+        match &shapes[0] {
+            Shape::Ellipse(ellipse) => assert_eq!(0.0, ellipse.angle),
+            other => panic!("expected an ellipse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_types_are_rejected() {
+        assert!(from_json(r#"[{"type": "hexagon", "side": 1.0}]"#).is_err());
+    }
+}
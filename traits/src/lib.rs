@@ -13,13 +13,77 @@
 // to define a set of behaviours necessary to accomplish
 // some purpose.
 
-use std::fmt::Display;
+// #[derive(Summary)] (see summary_derive) writes `::cool_lib::Summary`,
+// which only resolves in here if we can call ourselves cool_lib too
+extern crate self as cool_lib;
+
+mod collision;
+mod dimensions;
+mod feed;
+mod geometry;
+pub mod json;
+mod render;
+mod search;
+mod shapes;
+mod spatial;
+pub mod svg;
+mod thread;
+mod transform;
+
+pub use collision::Outline;
+pub use dimensions::Dimensions;
+pub use feed::{Feed, FeedError, FeedItem, Filter, Page};
+pub use geometry::{BoundingBox, Point};
+pub use render::{Format, Html, Markdown, PlainText, Renderer, SummaryParts};
+pub use search::{DocId, Hit, SearchIndex};
+pub use shapes::{Circle, Ellipse, Polygon, Shape};
+pub use spatial::{ShapeId, SpatialIndex};
+pub use thread::{Conversations, Thread};
+pub use transform::{Transform, Transformable};
+// The derive macro shares the trait's name, like serde's do, so one
+// import brings in both
+pub use summary_derive::Summary;
+
+use serde::{Deserialize, Serialize};
 
 pub trait Summary {
     fn summarize(&self) -> String;
     // A trait can have multiple methods in its body; the method
     // signatures are listed one per line and each line ends in
     // a semi-colon.
+
+    // The rest have default implementations (more on those below), so
+    // summarize() is all a type HAS to write.
+
+    // What the summary is made of, for renderers to lay out. By default
+    // all we have is the summarize() text.
+    fn parts(&self) -> SummaryParts {
+        SummaryParts {
+            body: self.summarize(),
+            ..SummaryParts::default()
+        }
+    }
+    fn summarize_author(&self) -> String {
+        self.parts()
+            .author
+            .unwrap_or_else(|| String::from("anonymous"))
+    }
+    // summarize(), cut down to at most `max` characters as a reader
+    // would count them
+    fn summarize_truncated(&self, max: usize) -> String {
+        render::truncate(&self.summarize(), max)
+    }
+    // Plain text, Markdown, HTML or a renderer of your own
+    fn render(&self, renderer: &dyn Renderer) -> String {
+        renderer.render(&self.parts())
+    }
+}
+
+// Summary's sibling: where Summary says how to show a type, Indexable
+// says what's worth searching in it (see search.rs). Each field comes
+// with a name so queries can pick one, like "author:ferris".
+pub trait Indexable {
+    fn fields(&self) -> Vec<(&'static str, String)>;
 }
 
 // Here, we declare a trait using the "trait" keyword and
@@ -51,19 +115,101 @@ impl Summary for NewsArticle {
     fn summarize(&self) -> String {
         format!("{}, by {}, ({})", self.headline, self.author, self.location)
     }
+    fn parts(&self) -> SummaryParts {
+        SummaryParts {
+            title: Some(self.headline.clone()),
+            author: Some(self.author.clone()),
+            location: Some(self.location.clone()),
+            body: self.content.clone(),
+        }
+    }
 }
 
+// Tweet ids only ever go up, so a bigger id means a later tweet
+pub type TweetId = u64;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Tweet {
+    pub id: TweetId,
     pub username: String,
     pub content: String,
-    pub reply: bool,
-    pub retweet: bool,
+    // The tweet this one answers, if it's a reply
+    pub in_reply_to: Option<TweetId>,
+    // The tweet this one passes on, if it's a retweet
+    pub retweet_of: Option<TweetId>,
+}
+
+impl Tweet {
+    pub fn new(id: TweetId, username: &str, content: &str) -> Tweet {
+        Tweet {
+            id,
+            username: username.to_string(),
+            content: content.to_string(),
+            in_reply_to: None,
+            retweet_of: None,
+        }
+    }
+
+    pub fn replying_to(mut self, parent: TweetId) -> Tweet {
+        self.in_reply_to = Some(parent);
+        self
+    }
+
+    pub fn retweeting(mut self, original: TweetId) -> Tweet {
+        self.retweet_of = Some(original);
+        self
+    }
+
+    pub fn is_reply(&self) -> bool {
+        self.in_reply_to.is_some()
+    }
+
+    pub fn is_retweet(&self) -> bool {
+        self.retweet_of.is_some()
+    }
 }
 
 impl Summary for Tweet {
     fn summarize(&self) -> String {
         format!("{}: {}", self.username, self.content)
     }
+    fn parts(&self) -> SummaryParts {
+        SummaryParts {
+            author: Some(self.username.clone()),
+            body: self.content.clone(),
+            ..SummaryParts::default()
+        }
+    }
+    // Only the content gets cut while there's room for the username;
+    // when there isn't, the whole thing is cut like any other summary
+    fn summarize_truncated(&self, max: usize) -> String {
+        let prefix = format!("{}: ", self.username);
+        let room = max.saturating_sub(render::width(&prefix));
+        if room == 0 {
+            return render::truncate(&self.summarize(), max);
+        }
+        format!("{}{}", prefix, render::truncate(&self.content, room))
+    }
+}
+
+impl Indexable for NewsArticle {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("headline", self.headline.clone()),
+            ("author", self.author.clone()),
+            ("location", self.location.clone()),
+            ("content", self.content.clone()),
+        ]
+    }
+}
+
+impl Indexable for Tweet {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("author", self.username.clone()),
+            ("content", self.content.clone()),
+        ]
+    }
 }
 
 // Going back to our above definitions, we mentioned that
@@ -80,23 +226,145 @@ impl Summary for Tweet {
 // body.
 
 // Let's have a look at how this can be achieved.
+
+// Rectangle and Triangle only know their sizes, so they live in their
+// own little frame: the bottom-left corner of the rectangle and the
+// left end of the triangle's base sit on the origin. The other shapes
+// (see shapes.rs) carry their own position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     pub width: f64,
     pub height: f64,
 }
 
+impl Rectangle {
+    // Anticlockwise from the origin
+    pub fn vertices(&self) -> [Point; 4] {
+        [
+            Point::new(0.0, 0.0),
+            Point::new(self.width, 0.0),
+            Point::new(self.width, self.height),
+            Point::new(0.0, self.height),
+        ]
+    }
+}
+
+// A base and a height on their own don't pin down a triangle, so we
+// treat it as isosceles - the apex sits right above the middle of the
+// base.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Triangle {
     pub base: f64,
     pub height: f64,
 }
 
+impl Triangle {
+    pub fn vertices(&self) -> [Point; 3] {
+        [
+            Point::new(0.0, 0.0),
+            Point::new(self.base, 0.0),
+            Point::new(self.base / 2.0, self.height),
+        ]
+    }
+}
+
 pub trait ShapeOperation {
     // No default - compiler will enforce any types that implement this trait
     // to provide its custom implementation for area() method
     fn area(&self) -> f64;
-    // Default implementation for this trait method
-    fn dimensions(&self) -> String {
-        format!("This method tells you about the dimensions of this shape!")
+    fn perimeter(&self) -> f64;
+    // The smallest axis-aligned box the shape fits in
+    fn bounding_box(&self) -> BoundingBox;
+    // Default implementation: the middle of the bounding box. That's
+    // spot on for anything symmetric, lopsided shapes override it.
+    fn centroid(&self) -> Point {
+        self.bounding_box().center()
+    }
+    // Default implementation for this trait method: all we know about
+    // an arbitrary shape is how big its bounding box is
+    fn dimensions(&self) -> Dimensions {
+        let bbox = self.bounding_box();
+        Dimensions::Bounds {
+            width: bbox.width(),
+            height: bbox.height(),
+        }
+    }
+    // How to draw the shape as a single SVG element, in the shape's own
+    // coordinates (svg.rs takes care of flipping the y axis). By
+    // default we can only draw the bounding box.
+    fn svg_element(&self) -> String {
+        let bbox = self.bounding_box();
+        svg::rect_element(bbox.min, bbox.width(), bbox.height())
+    }
+    // The exact outline used for collision checks (see collision.rs).
+    // Again all we know by default is the bounding box.
+    fn outline(&self) -> Outline {
+        let bbox = self.bounding_box();
+        Outline::Polygon(vec![
+            bbox.min,
+            Point::new(bbox.max.x, bbox.min.y),
+            bbox.max,
+            Point::new(bbox.min.x, bbox.max.y),
+        ])
+    }
+    // These three only need outline(), so they work between any two
+    // shapes - a rectangle and a triangle, a circle and a polygon...
+    // Taking &dyn ShapeOperation means both sides can be different types.
+    fn contains_point(&self, point: &Point) -> bool {
+        self.outline().contains_point(point)
+    }
+    fn intersects(&self, other: &dyn ShapeOperation) -> bool {
+        self.outline().intersects(&other.outline())
+    }
+    // Does `other` fit entirely inside this shape? This is the old
+    // Rectangle::can_hold check, except for any pair of shapes and with
+    // touching edges allowed.
+    fn contains_shape(&self, other: &dyn ShapeOperation) -> bool {
+        self.outline().contains(&other.outline())
+    }
+    // How far the point is from the shape, 0 if it's inside
+    fn distance_to(&self, point: &Point) -> f64 {
+        self.outline().distance_to(point)
+    }
+}
+
+// A boxed shape is still a shape. This lets a Vec<Box<dyn ShapeOperation>>
+// go anywhere a ShapeOperation type is expected, like a SpatialIndex.
+impl<T: ShapeOperation + ?Sized> ShapeOperation for Box<T> {
+    fn area(&self) -> f64 {
+        (**self).area()
+    }
+    fn perimeter(&self) -> f64 {
+        (**self).perimeter()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        (**self).bounding_box()
+    }
+    fn centroid(&self) -> Point {
+        (**self).centroid()
+    }
+    fn dimensions(&self) -> Dimensions {
+        (**self).dimensions()
+    }
+    fn svg_element(&self) -> String {
+        (**self).svg_element()
+    }
+    fn outline(&self) -> Outline {
+        (**self).outline()
+    }
+    // Forward these too, or a shape that overrides them would quietly
+    // fall back to the outline versions once it's boxed
+    fn contains_point(&self, point: &Point) -> bool {
+        (**self).contains_point(point)
+    }
+    fn intersects(&self, other: &dyn ShapeOperation) -> bool {
+        (**self).intersects(other)
+    }
+    fn contains_shape(&self, other: &dyn ShapeOperation) -> bool {
+        (**self).contains_shape(other)
+    }
+    fn distance_to(&self, point: &Point) -> f64 {
+        (**self).distance_to(point)
     }
 }
 
@@ -104,18 +372,54 @@ impl ShapeOperation for Rectangle {
     fn area(&self) -> f64 {
         self.width * self.height
     }
-    // Since we haven't explicitly provided a dimensions() method, it uses the
-    // default implementation provided by the trait method
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width + self.height)
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(0.0, 0.0), Point::new(self.width, self.height))
+    }
+    fn dimensions(&self) -> Dimensions {
+        Dimensions::Rectangle {
+            width: self.width,
+            height: self.height,
+        }
+    }
+    fn svg_element(&self) -> String {
+        svg::rect_element(Point::new(0.0, 0.0), self.width, self.height)
+    }
+    // Since we haven't explicitly provided a centroid() method, it uses
+    // the default implementation provided by the trait method
 }
 
 impl ShapeOperation for Triangle {
     fn area(&self) -> f64 {
         0.50 * self.base * self.height
     }
+    fn perimeter(&self) -> f64 {
+        let [a, b, c] = self.vertices();
+        a.distance(&b) + b.distance(&c) + c.distance(&a)
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(0.0, 0.0), Point::new(self.base, self.height))
+    }
+    // The centroid of a triangle is the average of its corners, which
+    // is NOT the middle of its bounding box - so we override it
+    fn centroid(&self) -> Point {
+        Point::new(self.base / 2.0, self.height / 3.0)
+    }
     // Here, we are opting to override the default behaviour of the
     // trait method!
-    fn dimensions(&self) -> String {
-        format!("Triangle | (Base: {}, Height: {})", self.base, self.height)
+    fn dimensions(&self) -> Dimensions {
+        Dimensions::Triangle {
+            base: self.base,
+            height: self.height,
+        }
+    }
+    fn svg_element(&self) -> String {
+        svg::polygon_element(&self.vertices())
+    }
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.vertices().to_vec())
     }
 }
 
@@ -139,7 +443,9 @@ pub fn get_shape_area(item: &impl ShapeOperation) -> f64 {
 // The above trait parameter type (i.e &impl ShapeOperation)
 // is actually syntactic sugar for the following:
 
-pub fn get_shape_area<T: ShapeOperation>(item: &T) {}
+pub fn get_shape_area_bound<T: ShapeOperation>(item: &T) -> f64 {
+    item.area()
+}
 
 // There are certain nuances with regards to the use of traits
 // as parameters that we need to keep in mind. Let's discuss them
@@ -147,21 +453,23 @@ pub fn get_shape_area<T: ShapeOperation>(item: &T) {}
 
 // In the above function, T can be of any type so long as it
 // implements the "ShapeOperation" trait. What if we had more than
-// one parameter? Sounds simple enough, let's update our function
-// definition (using the shorthand syntax):
+// one parameter? Sounds simple enough, let's write a function
+// that takes two (using the shorthand syntax):
 
-pub fn get_shape_area(item1: &impl ShapeOperation, item2: &impl ShapeOperation) {}
+pub fn get_total_area(item1: &impl ShapeOperation, item2: &impl ShapeOperation) -> f64 {
+    item1.area() + item2.area()
+}
 
-// That does it! Our "get_shape_area()" function now accepts
+// That does it! Our "get_total_area()" function accepts
 // two parameters of ANY type so long as they both implement
 // the ShapeOperation trait.
 
 // So, now we could call the function in the following ways:
 
-pub fn get_shape_area(triangle, rectangle);  // (A)
-pub fn get_shape_area(rectangle, rectangle); // (B)
-pub fn get_shape_area(triangle, triangle);   // (C)
-pub fn get_shape_area(rectangle, triangle);  // (D)
+// get_total_area(&triangle, &rectangle);  // (A)
+// get_total_area(&rectangle, &rectangle); // (B)
+// get_total_area(&triangle, &triangle);   // (C)
+// get_total_area(&rectangle, &triangle);  // (D)
 
 // As you can see our scope of types is very wide here.
 // What if we wanted to constrain the call to the SAME TYPE
@@ -169,10 +477,11 @@ pub fn get_shape_area(rectangle, triangle);  // (D)
 
 // This is where our syntactic sugar hits it's limitation.
 // To accomodate the above, we'll need to explicitly make use of
-// the trait bound syntax. Let's update our function definition
-// to see how we can accomodate this:
+// the trait bound syntax. Let's see how we can accomodate this:
 
-pub fn get_shape_area<T: ShapeOperation>(item1: &T, item2: &T) {}
+pub fn get_total_area_same<T: ShapeOperation>(item1: &T, item2: &T) -> f64 {
+    item1.area() + item2.area()
+}
 
 // Ta-da! The GENERIC TYPE "T" specified as the type for "item1" and
 // "item2" parameters constrains the function such that the CONCRETE
@@ -182,19 +491,278 @@ pub fn get_shape_area<T: ShapeOperation>(item1: &T, item2: &T) {}
 // HERE'S ANOTHER REALLY COOL THING YOU CAN DO
 
 // We can also conditionally implement a trait for any type
-// that implements another trait! Here's an example to help 
+// that implements another trait! Here's an example to help
 // explain
 
 // Snippet from the standard library...
-impl <T: Display> ToString for T {
-    fn to_string(&self) -> String {}
-}
+// impl<T: Display> ToString for T {
+//     fn to_string(&self) -> String { ... }
+// }
 
 // The above is what is called a "blanket implementation",
 // that is, we can call the "to_string()" method defined by
-// the "ToString" trait on any type that implements the 
+// the "ToString" trait on any type that implements the
 // "Display" trait!
 
 // Integers implement the "Display" trait, thus why, we can
 // call "to_string()" on it!
-println!(3.to_string());
\ No newline at end of file
+// println!("{}", 3.to_string());
+
+// Floating point sums rarely come out exactly equal, so tests all over
+// the crate compare with these instead. The slack grows with the size of
+// the number so big areas get the same precision as small ones.
+#[cfg(test)]
+pub(crate) fn close(expected: f64, actual: f64) -> bool {
+    (expected - actual).abs() <= 1e-9 * expected.abs().max(1.0)
+}
+
+#[cfg(test)]
+pub(crate) fn assert_close(expected: f64, actual: f64) {
+    assert!(
+        close(expected, actual),
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article() -> NewsArticle {
+        NewsArticle {
+            headline: String::from("Penguins win the Stanley Cup Championship!"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from("Iceburgh"),
+            content: String::from(
+                "The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            ),
+        }
+    }
+
+    fn tweet() -> Tweet {
+        Tweet::new(
+            1,
+            "horse_ebooks",
+            "of course, as you probably already know, people",
+        )
+    }
+
+    #[test]
+    fn summaries_keep_their_old_format() {
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship!, by Iceburgh, (Pittsburgh, PA, USA)",
+            article().summarize()
+        );
+        assert_eq!(
+            "horse_ebooks: of course, as you probably already know, people",
+            tweet().summarize()
+        );
+    }
+
+    #[test]
+    fn summary_defaults() {
+        // Only summarize() written, everything else comes for free
+        struct Note;
+        impl Summary for Note {
+            fn summarize(&self) -> String {
+                String::from("Buy milk")
+            }
+        }
+        assert_eq!("anonymous", Note.summarize_author());
+        assert_eq!("Buy…", Note.summarize_truncated(4));
+        assert_eq!("Buy milk", Note.render(&PlainText));
+        assert_eq!("Iceburgh", article().summarize_author());
+        assert_eq!("horse_ebooks", tweet().summarize_author());
+    }
+
+    #[test]
+    fn truncated_summaries() {
+        assert_eq!("Penguins win…", article().summarize_truncated(13));
+        // The username survives as long as there's room for it
+        assert_eq!("horse_ebooks: of…", tweet().summarize_truncated(17));
+        assert_eq!("horse_ebooks: …", tweet().summarize_truncated(15));
+        assert_eq!("ho…", tweet().summarize_truncated(3));
+        for max in 0..70 {
+            assert!(tweet().summarize_truncated(max).chars().count() <= max);
+        }
+    }
+
+    #[test]
+    fn any_summary_renders_in_any_format() {
+        let items: Vec<Box<dyn Summary>> = vec![Box::new(article()), Box::new(tweet())];
+        let html: Vec<String> = items.iter().map(|item| item.render(&Html)).collect();
+        assert!(html[0].starts_with("<article class=\"summary\"><h2>Penguins win"));
+        assert!(html[1].contains("<p class=\"byline\">by horse_ebooks</p>"));
+        let markdown = items[0].render(&Format::Markdown);
+        assert!(markdown.contains("*by Iceburgh, Pittsburgh, PA, USA*"));
+    }
+
+    #[test]
+    fn derived_summaries() {
+        #[derive(Summary)]
+        #[summary(template = "{headline}, by {author}, ({location})")]
+        struct Story {
+            headline: String,
+            author: String,
+            location: String,
+        }
+        let story = Story {
+            headline: String::from("Penguins win the Stanley Cup Championship!"),
+            author: String::from("Iceburgh"),
+            location: String::from("Pittsburgh, PA, USA"),
+        };
+        // Same as the hand-written NewsArticle one
+        assert_eq!(article().summarize(), story.summarize());
+
+        #[derive(Summary)]
+        struct Song {
+            title: String,
+            #[summary(skip)]
+            #[allow(dead_code)]
+            plays: u64,
+            #[summary(order = 1)]
+            artist: &'static str,
+        }
+        let song = Song {
+            title: String::from("Peaches en Regalia"),
+            plays: 42,
+            artist: "Zappa",
+        };
+        assert_eq!("Zappa, Peaches en Regalia", song.summarize());
+        // Derived summaries get the defaults like any other
+        assert_eq!("anonymous", song.summarize_author());
+
+        #[derive(Summary)]
+        #[summary(template = "{{{score:>3}}} {name} {name}")]
+        struct Score<T: std::fmt::Display> {
+            name: T,
+            score: u32,
+        }
+        let score = Score {
+            name: 'x',
+            score: 7,
+        };
+        assert_eq!("{  7} x x", score.summarize());
+    }
+
+    #[test]
+    fn rectangle() {
+        let rectangle = Rectangle {
+            width: 4.0,
+            height: 3.0,
+        };
+        assert_close(12.0, rectangle.area());
+        assert_close(14.0, rectangle.perimeter());
+        assert_eq!(
+            BoundingBox::new(Point::new(0.0, 0.0), Point::new(4.0, 3.0)),
+            rectangle.bounding_box()
+        );
+        assert_eq!(Point::new(2.0, 1.5), rectangle.centroid());
+        assert_eq!(
+            "Rectangle | (Width: 4, Height: 3)",
+            rectangle.dimensions().to_string()
+        );
+    }
+
+    #[test]
+    fn triangle() {
+        let triangle = Triangle {
+            base: 6.0,
+            height: 4.0,
+        };
+        assert_close(12.0, triangle.area());
+        // Two slanted sides of sqrt(3^2 + 4^2) = 5 plus the base
+        assert_close(16.0, triangle.perimeter());
+        assert_eq!(Point::new(6.0, 4.0), triangle.bounding_box().max);
+        assert_eq!(Point::new(3.0, 4.0 / 3.0), triangle.centroid());
+        assert_eq!(
+            Dimensions::Triangle {
+                base: 6.0,
+                height: 4.0
+            },
+            triangle.dimensions()
+        );
+    }
+
+    #[test]
+    fn default_dimensions_come_from_the_bounding_box() {
+        // A shape that only implements the required methods
+        struct Dot;
+        impl ShapeOperation for Dot {
+            fn area(&self) -> f64 {
+                0.0
+            }
+            fn perimeter(&self) -> f64 {
+                0.0
+            }
+            fn bounding_box(&self) -> BoundingBox {
+                BoundingBox::new(Point::new(1.0, 1.0), Point::new(1.5, 2.0))
+            }
+        }
+        assert_eq!(
+            Dimensions::Bounds {
+                width: 0.5,
+                height: 1.0
+            },
+            Dot.dimensions()
+        );
+        assert_eq!(Point::new(1.25, 1.5), Dot.centroid());
+    }
+
+    #[test]
+    fn trait_parameters_accept_any_shape() {
+        let rectangle = Rectangle {
+            width: 2.0,
+            height: 2.0,
+        };
+        let triangle = Triangle {
+            base: 2.0,
+            height: 2.0,
+        };
+        assert_close(4.0, get_shape_area(&rectangle));
+        assert_close(2.0, get_shape_area_bound(&triangle));
+        assert_close(6.0, get_total_area(&rectangle, &triangle));
+        assert_close(8.0, get_total_area_same(&rectangle, &rectangle));
+    }
+
+    #[test]
+    fn boxed_shapes_keep_their_own_collision_checks() {
+        // Its outline is a unit square, but it claims to be everywhere
+        struct Everywhere;
+        impl ShapeOperation for Everywhere {
+            fn area(&self) -> f64 {
+                f64::INFINITY
+            }
+            fn perimeter(&self) -> f64 {
+                0.0
+            }
+            fn bounding_box(&self) -> BoundingBox {
+                BoundingBox::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0))
+            }
+            fn contains_point(&self, _: &Point) -> bool {
+                true
+            }
+            fn intersects(&self, _: &dyn ShapeOperation) -> bool {
+                true
+            }
+            fn contains_shape(&self, _: &dyn ShapeOperation) -> bool {
+                true
+            }
+            fn distance_to(&self, _: &Point) -> f64 {
+                0.0
+            }
+        }
+        let far = Point::new(10.0, 10.0);
+        let big = Rectangle {
+            width: 5.0,
+            height: 5.0,
+        };
+        let boxed: Box<dyn ShapeOperation> = Box::new(Everywhere);
+        assert!(boxed.contains_point(&far));
+        assert!(boxed.intersects(&big));
+        assert!(boxed.contains_shape(&big));
+        assert_eq!(0.0, boxed.distance_to(&far));
+    }
+}
@@ -0,0 +1,237 @@
+// Turning a summary into text. Types that implement Summary describe
+// what they're made of (SummaryParts) and a Renderer decides what that
+// looks like, so a new output format doesn't mean touching every type
+// and a new type gets every format for free.
+
+use std::{fmt, str::FromStr};
+use unicode_segmentation::UnicodeSegmentation;
+
+// The pieces of a summary, before any formatting. Everything but the
+// body is optional.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SummaryParts {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub location: Option<String>,
+    pub body: String,
+}
+
+impl SummaryParts {
+    // "by Jane Doe, Sydney" - whichever of the two we have
+    fn byline(&self) -> Option<String> {
+        match (&self.author, &self.location) {
+            (Some(author), Some(location)) => Some(format!("by {}, {}", author, location)),
+            (Some(author), None) => Some(format!("by {}", author)),
+            (None, Some(location)) => Some(location.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+pub trait Renderer {
+    fn render(&self, parts: &SummaryParts) -> String;
+}
+
+// Title, byline and body on their own lines, nothing else
+pub struct PlainText;
+
+impl Renderer for PlainText {
+    fn render(&self, parts: &SummaryParts) -> String {
+        let lines: Vec<String> = [
+            parts.title.clone(),
+            parts.byline(),
+            Some(parts.body.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|line| !line.is_empty())
+        .collect();
+        lines.join("\n")
+    }
+}
+
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn render(&self, parts: &SummaryParts) -> String {
+        let mut blocks = Vec::new();
+        if let Some(title) = &parts.title {
+            blocks.push(format!("## {}", escape_markdown(title)));
+        }
+        if let Some(byline) = parts.byline() {
+            blocks.push(format!("*{}*", escape_markdown(&byline)));
+        }
+        if !parts.body.is_empty() {
+            blocks.push(escape_markdown(&parts.body));
+        }
+        blocks.join("\n\n") + "\n"
+    }
+}
+
+pub struct Html;
+
+impl Renderer for Html {
+    fn render(&self, parts: &SummaryParts) -> String {
+        let mut html = String::from("<article class=\"summary\">");
+        if let Some(title) = &parts.title {
+            html += &format!("<h2>{}</h2>", escape_html(title));
+        }
+        if let Some(byline) = parts.byline() {
+            html += &format!("<p class=\"byline\">{}</p>", escape_html(&byline));
+        }
+        if !parts.body.is_empty() {
+            html += &format!("<p>{}</p>", escape_html(&parts.body));
+        }
+        html + "</article>"
+    }
+}
+
+// The built-in renderers by name, for picking one at runtime (from a
+// command line flag, say)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Plain,
+    Markdown,
+    Html,
+}
+
+impl Renderer for Format {
+    fn render(&self, parts: &SummaryParts) -> String {
+        match self {
+            Format::Plain => PlainText.render(parts),
+            Format::Markdown => Markdown.render(parts),
+            Format::Html => Html.render(parts),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Format::Plain => "plain",
+            Format::Markdown => "markdown",
+            Format::Html => "html",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.trim().to_lowercase().as_str() {
+            "plain" | "text" | "txt" => Ok(Format::Plain),
+            "markdown" | "md" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            other => Err(format!("unknown format '{}'", other)),
+        }
+    }
+}
+
+// How long the text is as a reader would count it
+pub(crate) fn width(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+// Cut the text down to at most `max` user-perceived characters
+// (graphemes), ending with an ellipsis when anything was cut. Counting
+// chars instead would happily split an accent from its letter or a
+// family emoji into its members.
+pub(crate) fn truncate(text: &str, max: usize) -> String {
+    let graphemes: Vec<&str> = text.graphemes(true).collect();
+    if graphemes.len() <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    // Leave room for the ellipsis and don't end on a dangling space
+    let kept = graphemes[..max - 1].concat();
+    format!("{}…", kept.trim_end())
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]#<>|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article() -> SummaryParts {
+        SummaryParts {
+            title: Some(String::from("Penguins <3 fish")),
+            author: Some(String::from("Iceburgh")),
+            location: Some(String::from("Pittsburgh")),
+            body: String::from("The *best* fish & chips"),
+        }
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            "Penguins <3 fish\nby Iceburgh, Pittsburgh\nThe *best* fish & chips",
+            PlainText.render(&article())
+        );
+        let bare = SummaryParts {
+            body: String::from("just this"),
+            ..SummaryParts::default()
+        };
+        assert_eq!("just this", PlainText.render(&bare));
+    }
+
+    #[test]
+    fn markdown_escapes_formatting_characters() {
+        assert_eq!(
+            "## Penguins \\<3 fish\n\n*by Iceburgh, Pittsburgh*\n\nThe \\*best\\* fish & chips\n",
+            Markdown.render(&article())
+        );
+    }
+
+    #[test]
+    fn html_escapes_markup() {
+        assert_eq!(
+            "<article class=\"summary\"><h2>Penguins &lt;3 fish</h2>\
+             <p class=\"byline\">by Iceburgh, Pittsburgh</p>\
+             <p>The *best* fish &amp; chips</p></article>",
+            Html.render(&article())
+        );
+    }
+
+    #[test]
+    fn formats_by_name() {
+        assert_eq!(Ok(Format::Markdown), "md".parse());
+        assert_eq!(Ok(Format::Html), " HTML ".parse());
+        assert!("pdf".parse::<Format>().is_err());
+        assert_eq!(Html.render(&article()), Format::Html.render(&article()));
+        assert_eq!("plain", Format::default().to_string());
+    }
+
+    #[test]
+    fn truncation_respects_graphemes() {
+        assert_eq!("short", truncate("short", 5));
+        assert_eq!("shor…", truncate("shorter", 5));
+        assert_eq!("a…", truncate("a bit", 3));
+        assert_eq!("", truncate("anything", 0));
+        // e + combining acute accent is one grapheme, so is the family
+        let text = "cafe\u{301} 👨‍👩‍👧 time";
+        assert_eq!("cafe\u{301}…", truncate(text, 6));
+        assert_eq!("cafe\u{301} 👨‍👩‍👧…", truncate(text, 7));
+    }
+}
@@ -0,0 +1,414 @@
+// Full-text search over anything Indexable.
+//
+// Adding a document breaks each of its fields into words (tokens),
+// drops the ones too common to mean anything, boils the rest down to
+// their stem ("running" and "runs" both become "run") and notes where
+// each one appeared. That gives an inverted index: for every stem, the
+// documents and positions it turns up at.
+//
+// Results are ranked with BM25, which rewards documents that use the
+// query's words a lot - more so for rare words, and with diminishing
+// returns - while not letting long documents win just by being long.
+//
+// A query is a list of words, any of which may match. On top of that:
+//   "quoted words"  must all appear, next to each other and in order
+//   field:word      only counts the word in that field (e.g. author:ferris)
+
+use crate::Indexable;
+use rust_stemmers::{Algorithm, Stemmer};
+use std::collections::HashMap;
+
+// Standard BM25 tuning: how quickly repeats stop counting (K1) and how
+// much to hold a document's length against it (B)
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "no", "not", "of", "on", "or", "such", "that", "the", "their", "then", "there", "these",
+    "they", "this", "to", "was", "will", "with",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DocId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub id: DocId,
+    pub score: f64,
+}
+
+// Where one stem shows up in one field of one document
+struct Posting {
+    doc: DocId,
+    field: &'static str,
+    positions: Vec<usize>,
+}
+
+pub struct SearchIndex<T> {
+    documents: Vec<T>,
+    // Word count per document, stop words left out
+    lengths: Vec<usize>,
+    postings: HashMap<String, Vec<Posting>>,
+    stemmer: Stemmer,
+}
+
+// A word as it was in the text and the stem it comes down to, with its
+// position counting every word (stop words included, so phrases with a
+// "the" in the middle still line up)
+struct Token {
+    position: usize,
+    stem: String,
+}
+
+impl<T: Indexable> SearchIndex<T> {
+    pub fn new() -> SearchIndex<T> {
+        SearchIndex {
+            documents: Vec::new(),
+            lengths: Vec::new(),
+            postings: HashMap::new(),
+            stemmer: Stemmer::create(Algorithm::English),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn get(&self, id: DocId) -> Option<&T> {
+        self.documents.get(id.0)
+    }
+
+    pub fn add(&mut self, document: T) -> DocId {
+        let id = DocId(self.documents.len());
+        let mut length = 0;
+        for (field, text) in document.fields() {
+            let mut positions: HashMap<String, Vec<usize>> = HashMap::new();
+            for token in self.tokenize(&text) {
+                positions
+                    .entry(token.stem)
+                    .or_default()
+                    .push(token.position);
+                length += 1;
+            }
+            for (stem, positions) in positions {
+                self.postings.entry(stem).or_default().push(Posting {
+                    doc: id,
+                    field,
+                    positions,
+                });
+            }
+        }
+        self.documents.push(document);
+        self.lengths.push(length);
+        id
+    }
+
+    // Best match first. Documents that match nothing aren't included.
+    pub fn search(&self, query: &str) -> Vec<Hit> {
+        let query = self.parse(query);
+        let mut scores: HashMap<DocId, f64> = HashMap::new();
+        for term in query.terms.iter().chain(query.phrases.iter().flatten()) {
+            for (doc, score) in self.score(term) {
+                *scores.entry(doc).or_insert(0.0) += score;
+            }
+        }
+        let mut hits: Vec<Hit> = scores
+            .into_iter()
+            .filter(|(doc, _)| {
+                query
+                    .phrases
+                    .iter()
+                    .all(|phrase| self.has_phrase(*doc, phrase))
+            })
+            .map(|(id, score)| Hit { id, score })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits
+    }
+
+    // Just the documents, best match first
+    pub fn find(&self, query: &str) -> Vec<&T> {
+        self.search(query)
+            .into_iter()
+            .filter_map(|hit| self.get(hit.id))
+            .collect()
+    }
+
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let words = text
+            .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’'))
+            .map(|word| {
+                let word = word.to_lowercase().replace('’', "'");
+                let word = word.trim_matches('\'');
+                word.strip_suffix("'s").unwrap_or(word).replace('\'', "")
+            })
+            .filter(|word| !word.is_empty());
+        words
+            .enumerate()
+            .filter(|(_, word)| !STOP_WORDS.contains(&word.as_str()))
+            .map(|(position, word)| Token {
+                position,
+                stem: self.stemmer.stem(&word).into_owned(),
+            })
+            .collect()
+    }
+
+    fn parse(&self, query: &str) -> Query {
+        let mut parsed = Query {
+            terms: Vec::new(),
+            phrases: Vec::new(),
+        };
+        // Every other piece between quotes is a phrase
+        for (i, piece) in query.split('"').enumerate() {
+            if i % 2 == 1 {
+                let tokens = self.tokenize(piece);
+                // Count from the first real word, which isn't the first
+                // word when the phrase starts with "the" or the like
+                let first = tokens.first().map_or(0, |token| token.position);
+                let phrase: Vec<Term> = tokens
+                    .into_iter()
+                    .map(|token| Term {
+                        field: None,
+                        stem: token.stem,
+                        offset: token.position - first,
+                    })
+                    .collect();
+                if !phrase.is_empty() {
+                    parsed.phrases.push(phrase);
+                }
+                continue;
+            }
+            for word in piece.split_whitespace() {
+                let (field, word) = match word.split_once(':') {
+                    Some((field, word)) if !field.is_empty() => (Some(field.to_lowercase()), word),
+                    _ => (None, word),
+                };
+                parsed
+                    .terms
+                    .extend(self.tokenize(word).into_iter().map(|token| Term {
+                        field: field.clone(),
+                        stem: token.stem,
+                        offset: 0,
+                    }));
+            }
+        }
+        parsed
+    }
+
+    // BM25 for one term against every document that has it
+    fn score(&self, term: &Term) -> HashMap<DocId, f64> {
+        let mut frequencies: HashMap<DocId, usize> = HashMap::new();
+        for posting in self.postings.get(&term.stem).into_iter().flatten() {
+            if term
+                .field
+                .as_ref()
+                .is_none_or(|field| field.as_str() == posting.field)
+            {
+                *frequencies.entry(posting.doc).or_insert(0) += posting.positions.len();
+            }
+        }
+        let total = self.documents.len() as f64;
+        let average = self.lengths.iter().sum::<usize>() as f64 / total.max(1.0);
+        let containing = frequencies.len() as f64;
+        let idf = (1.0 + (total - containing + 0.5) / (containing + 0.5)).ln();
+        frequencies
+            .into_iter()
+            .map(|(doc, frequency)| {
+                let frequency = frequency as f64;
+                let length = self.lengths[doc.0] as f64 / average.max(1.0);
+                let score =
+                    idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length));
+                (doc, score)
+            })
+            .collect()
+    }
+
+    // Whether all the phrase's words appear in one field of the
+    // document, spaced out exactly as in the query
+    fn has_phrase(&self, doc: DocId, phrase: &[Term]) -> bool {
+        let positions = |term: &Term| -> Vec<(&'static str, &[usize])> {
+            self.postings
+                .get(&term.stem)
+                .into_iter()
+                .flatten()
+                .filter(|posting| posting.doc == doc)
+                .map(|posting| (posting.field, posting.positions.as_slice()))
+                .collect()
+        };
+        let (first, rest) = match phrase.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+        // `first` sits at offset 0, everything else is counted from it
+        positions(first).into_iter().any(|(field, starts)| {
+            starts.iter().any(|start| {
+                rest.iter().all(|term| {
+                    positions(term)
+                        .iter()
+                        .any(|(other, at)| *other == field && at.contains(&(start + term.offset)))
+                })
+            })
+        })
+    }
+}
+
+impl<T: Indexable> Default for SearchIndex<T> {
+    fn default() -> SearchIndex<T> {
+        SearchIndex::new()
+    }
+}
+
+impl<T: Indexable> FromIterator<T> for SearchIndex<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(documents: I) -> SearchIndex<T> {
+        let mut index = SearchIndex::new();
+        for document in documents {
+            index.add(document);
+        }
+        index
+    }
+}
+
+struct Term {
+    // Only look in this field
+    field: Option<String>,
+    stem: String,
+    // Where it sits in its phrase, counting from the first word that
+    // isn't a stop word
+    offset: usize,
+}
+
+struct Query {
+    terms: Vec<Term>,
+    phrases: Vec<Vec<Term>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NewsArticle, Tweet};
+
+    fn article(headline: &str, author: &str, content: &str) -> NewsArticle {
+        NewsArticle {
+            headline: headline.to_string(),
+            location: String::from("Pittsburgh"),
+            author: author.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn articles() -> SearchIndex<NewsArticle> {
+        vec![
+            article(
+                "Penguins win the cup",
+                "Iceburgh",
+                "The Penguins won again. Winning is a habit for the Penguins.",
+            ),
+            article(
+                "Local zoo welcomes penguin chicks",
+                "Jane Doe",
+                "Three chicks hatched this week at the zoo.",
+            ),
+            article(
+                "State of the art rink opens",
+                "Iceburgh",
+                "The new rink is state of the art and seats thousands of hockey fans.",
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn headlines(index: &SearchIndex<NewsArticle>, query: &str) -> Vec<String> {
+        index
+            .find(query)
+            .into_iter()
+            .map(|article| article.headline.clone())
+            .collect()
+    }
+
+    #[test]
+    fn stems_and_drops_stop_words() {
+        let index: SearchIndex<NewsArticle> = SearchIndex::new();
+        let stems: Vec<String> = index
+            .tokenize("The Penguins' winning runs, Iceburgh's don't!")
+            .into_iter()
+            .map(|token| token.stem)
+            .collect();
+        assert_eq!(vec!["penguin", "win", "run", "iceburgh", "dont"], stems);
+    }
+
+    #[test]
+    fn ranks_by_bm25() {
+        let index = articles();
+        // "penguin" is in two articles, but all over the first one
+        assert_eq!(
+            vec!["Penguins win the cup", "Local zoo welcomes penguin chicks"],
+            headlines(&index, "penguins")
+        );
+        let hits = index.search("penguins");
+        assert!(hits[0].score > hits[1].score);
+        // A rarer word counts for more than a common one
+        assert_eq!(
+            "Local zoo welcomes penguin chicks",
+            headlines(&index, "penguin chick")[0]
+        );
+        assert!(index.search("basketball").is_empty());
+        assert!(index.search("").is_empty());
+    }
+
+    #[test]
+    fn phrases_must_appear_in_order() {
+        let index = articles();
+        assert_eq!(
+            vec!["State of the art rink opens"],
+            headlines(&index, "\"state of the art\"")
+        );
+        assert!(index.search("\"art of the state\"").is_empty());
+        // Both words are in the first article, but not side by side
+        assert!(index.search("\"penguins cup\"").is_empty());
+        assert_eq!(
+            vec!["Penguins win the cup"],
+            headlines(&index, "\"penguins won\" zoo")
+        );
+        assert_eq!(
+            vec!["Penguins win the cup"],
+            headlines(&index, "\"the penguins won\"")
+        );
+        assert_eq!(
+            vec!["Penguins win the cup"],
+            headlines(&index, "\"the penguins win\"")
+        );
+    }
+
+    #[test]
+    fn fields_can_be_searched_on_their_own() {
+        let index = articles();
+        assert_eq!(2, index.search("author:iceburgh").len());
+        assert_eq!(
+            vec!["Local zoo welcomes penguin chicks"],
+            headlines(&index, "headline:zoo")
+        );
+        assert!(index.search("headline:hockey").is_empty());
+        assert_eq!(1, index.search("hockey").len());
+    }
+
+    #[test]
+    fn tweets_are_indexable_too() {
+        let index: SearchIndex<Tweet> = vec![
+            Tweet::new(1, "@ferris", "Learning Rust traits today"),
+            Tweet::new(2, "@corro", "Unsafe code is fun"),
+            Tweet::new(3, "@ferris", "Traits are great"),
+        ]
+        .into_iter()
+        .collect();
+        let ids: Vec<u64> = index.find("trait").iter().map(|tweet| tweet.id).collect();
+        assert_eq!(2, ids.len());
+        assert!(ids.contains(&1) && ids.contains(&3));
+        assert_eq!(2, index.find("author:corro")[0].id);
+    }
+}
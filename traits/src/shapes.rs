@@ -0,0 +1,379 @@
+// Shapes beyond the original Rectangle and Triangle. Unlike those two,
+// each of these knows where it sits in the plane.
+
+use crate::{svg, BoundingBox, Dimensions, Outline, Point, Rectangle, ShapeOperation, Triangle};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl ShapeOperation for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(self.center.x - self.radius, self.center.y - self.radius),
+            Point::new(self.center.x + self.radius, self.center.y + self.radius),
+        )
+    }
+    fn dimensions(&self) -> Dimensions {
+        Dimensions::Circle {
+            radius: self.radius,
+        }
+    }
+    fn svg_element(&self) -> String {
+        format!(
+            r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+            self.center.x, self.center.y, self.radius
+        )
+    }
+    fn outline(&self) -> Outline {
+        Outline::Circle {
+            center: self.center,
+            radius: self.radius,
+        }
+    }
+}
+
+// An ellipse with radius_x along the x axis and radius_y along y, then
+// turned `angle` radians anticlockwise about its center. Older saved
+// shapes have no angle, which just means it isn't turned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ellipse {
+    pub center: Point,
+    pub radius_x: f64,
+    pub radius_y: f64,
+    #[serde(default)]
+    pub angle: f64,
+}
+
+impl ShapeOperation for Ellipse {
+    fn area(&self) -> f64 {
+        PI * self.radius_x * self.radius_y
+    }
+    // There's no closed form for the perimeter of an ellipse. Ramanujan's
+    // second approximation is exact for circles and within a tiny
+    // fraction of a percent for everything else.
+    fn perimeter(&self) -> f64 {
+        let (a, b) = (self.radius_x, self.radius_y);
+        if a + b == 0.0 {
+            return 0.0;
+        }
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        // How far the turned ellipse reaches along x and along y
+        let (sin, cos) = self.angle.sin_cos();
+        let half_width = (self.radius_x * cos).hypot(self.radius_y * sin);
+        let half_height = (self.radius_x * sin).hypot(self.radius_y * cos);
+        BoundingBox::new(
+            Point::new(self.center.x - half_width, self.center.y - half_height),
+            Point::new(self.center.x + half_width, self.center.y + half_height),
+        )
+    }
+    fn dimensions(&self) -> Dimensions {
+        Dimensions::Ellipse {
+            radius_x: self.radius_x,
+            radius_y: self.radius_y,
+            angle: self.angle,
+        }
+    }
+    fn svg_element(&self) -> String {
+        let element = format!(
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}""#,
+            self.center.x, self.center.y, self.radius_x, self.radius_y
+        );
+        if self.angle == 0.0 {
+            return element + "/>";
+        }
+        format!(
+            r#"{} transform="rotate({} {} {})"/>"#,
+            element,
+            self.angle.to_degrees(),
+            self.center.x,
+            self.center.y
+        )
+    }
+    fn outline(&self) -> Outline {
+        Outline::Ellipse {
+            center: self.center,
+            radius_x: self.radius_x,
+            radius_y: self.radius_y,
+            angle: self.angle,
+        }
+    }
+}
+
+// A simple (non self-intersecting) polygon. The vertices can go either
+// way round; the last one joins back up with the first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polygon {
+    pub vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Polygon {
+        Polygon { vertices }
+    }
+
+    // Pairs of consecutive vertices, including the closing edge
+    pub fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    // Positive for counter-clockwise vertices, negative for clockwise
+    fn signed_area(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum::<f64>()
+            / 2.0
+    }
+}
+
+impl ShapeOperation for Polygon {
+    // The shoelace formula
+    fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(a, b)| a.distance(b)).sum()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::around(&self.vertices)
+    }
+    fn centroid(&self) -> Point {
+        let area = self.signed_area();
+        // A flat polygon has no proper centroid, so settle for the
+        // average of its vertices
+        if area == 0.0 {
+            let n = self.vertices.len().max(1) as f64;
+            return Point::new(
+                self.vertices.iter().map(|v| v.x).sum::<f64>() / n,
+                self.vertices.iter().map(|v| v.y).sum::<f64>() / n,
+            );
+        }
+        let (x, y) = self.edges().fold((0.0, 0.0), |(x, y), (a, b)| {
+            let cross = a.x * b.y - b.x * a.y;
+            (x + (a.x + b.x) * cross, y + (a.y + b.y) * cross)
+        });
+        Point::new(x / (6.0 * area), y / (6.0 * area))
+    }
+    fn dimensions(&self) -> Dimensions {
+        let bbox = self.bounding_box();
+        Dimensions::Polygon {
+            vertices: self.vertices.len(),
+            width: bbox.width(),
+            height: bbox.height(),
+        }
+    }
+    fn svg_element(&self) -> String {
+        svg::polygon_element(&self.vertices)
+    }
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.vertices.clone())
+    }
+}
+
+// Every shape the library knows about, in one type. This is what gets
+// saved and loaded - a Box<dyn ShapeOperation> can't be deserialised
+// because serde wouldn't know which concrete type to build, so the
+// "type" tag in the JSON tells it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    Rectangle(Rectangle),
+    Triangle(Triangle),
+    Circle(Circle),
+    Ellipse(Ellipse),
+    Polygon(Polygon),
+}
+
+impl Shape {
+    fn inner(&self) -> &dyn ShapeOperation {
+        match self {
+            Shape::Rectangle(shape) => shape,
+            Shape::Triangle(shape) => shape,
+            Shape::Circle(shape) => shape,
+            Shape::Ellipse(shape) => shape,
+            Shape::Polygon(shape) => shape,
+        }
+    }
+
+    pub fn into_boxed(self) -> Box<dyn ShapeOperation> {
+        match self {
+            Shape::Rectangle(shape) => Box::new(shape),
+            Shape::Triangle(shape) => Box::new(shape),
+            Shape::Circle(shape) => Box::new(shape),
+            Shape::Ellipse(shape) => Box::new(shape),
+            Shape::Polygon(shape) => Box::new(shape),
+        }
+    }
+}
+
+impl ShapeOperation for Shape {
+    fn area(&self) -> f64 {
+        self.inner().area()
+    }
+    fn perimeter(&self) -> f64 {
+        self.inner().perimeter()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        self.inner().bounding_box()
+    }
+    fn centroid(&self) -> Point {
+        self.inner().centroid()
+    }
+    fn dimensions(&self) -> Dimensions {
+        self.inner().dimensions()
+    }
+    fn svg_element(&self) -> String {
+        self.inner().svg_element()
+    }
+    fn outline(&self) -> Outline {
+        self.inner().outline()
+    }
+}
+
+impl From<Rectangle> for Shape {
+    fn from(shape: Rectangle) -> Shape {
+        Shape::Rectangle(shape)
+    }
+}
+
+impl From<Triangle> for Shape {
+    fn from(shape: Triangle) -> Shape {
+        Shape::Triangle(shape)
+    }
+}
+
+impl From<Circle> for Shape {
+    fn from(shape: Circle) -> Shape {
+        Shape::Circle(shape)
+    }
+}
+
+impl From<Ellipse> for Shape {
+    fn from(shape: Ellipse) -> Shape {
+        Shape::Ellipse(shape)
+    }
+}
+
+impl From<Polygon> for Shape {
+    fn from(shape: Polygon) -> Shape {
+        Shape::Polygon(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_close;
+
+    #[test]
+    fn circle() {
+        let circle = Circle {
+            center: Point::new(1.0, 2.0),
+            radius: 2.0,
+        };
+        assert_close(4.0 * PI, circle.area());
+        assert_close(4.0 * PI, circle.perimeter());
+        assert_eq!(
+            BoundingBox::new(Point::new(-1.0, 0.0), Point::new(3.0, 4.0)),
+            circle.bounding_box()
+        );
+        assert_eq!(Point::new(1.0, 2.0), circle.centroid());
+        assert_eq!(Dimensions::Circle { radius: 2.0 }, circle.dimensions());
+    }
+
+    #[test]
+    fn ellipse() {
+        let ellipse = Ellipse {
+            center: Point::new(0.0, 0.0),
+            radius_x: 3.0,
+            radius_y: 2.0,
+            angle: 0.0,
+        };
+        assert_close(6.0 * PI, ellipse.area());
+        // Reference value from the exact elliptic integral
+        assert!((ellipse.perimeter() - 15.865_439_589).abs() < 1e-6);
+        assert_eq!(Point::new(3.0, 2.0), ellipse.bounding_box().max);
+        assert_eq!(Point::new(0.0, 0.0), ellipse.centroid());
+        assert_eq!(
+            Dimensions::Ellipse {
+                radius_x: 3.0,
+                radius_y: 2.0,
+                angle: 0.0
+            },
+            ellipse.dimensions()
+        );
+
+        // A round ellipse is just a circle
+        let round = Ellipse {
+            center: Point::new(0.0, 0.0),
+            radius_x: 1.0,
+            radius_y: 1.0,
+            angle: 0.0,
+        };
+        assert_close(2.0 * PI, round.perimeter());
+    }
+
+    #[test]
+    fn polygon() {
+        // An L shape, listed clockwise to make sure that doesn't matter
+        let polygon = Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 2.0),
+            Point::new(1.0, 2.0),
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 1.0),
+            Point::new(2.0, 0.0),
+        ]);
+        assert_close(3.0, polygon.area());
+        assert_close(8.0, polygon.perimeter());
+        assert_eq!(
+            BoundingBox::new(Point::new(0.0, 0.0), Point::new(2.0, 2.0)),
+            polygon.bounding_box()
+        );
+        // Three unit squares centred on (0.5, 0.5), (0.5, 1.5), (1.5, 0.5)
+        let centroid = polygon.centroid();
+        assert_close(5.0 / 6.0, centroid.x);
+        assert_close(5.0 / 6.0, centroid.y);
+        assert_eq!(
+            Dimensions::Polygon {
+                vertices: 6,
+                width: 2.0,
+                height: 2.0
+            },
+            polygon.dimensions()
+        );
+    }
+
+    #[test]
+    fn shape_enum_delegates_to_the_real_shape() {
+        let shape = Shape::from(Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        });
+        assert_close(PI, shape.area());
+        assert_eq!(Dimensions::Circle { radius: 1.0 }, shape.dimensions());
+        assert_close(2.0 * PI, shape.into_boxed().perimeter());
+    }
+
+    #[test]
+    fn flat_polygon_falls_back_to_vertex_average() {
+        let line = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)]);
+        assert_close(0.0, line.area());
+        assert_eq!(Point::new(2.0, 0.0), line.centroid());
+    }
+}
@@ -0,0 +1,586 @@
+// Finding shapes by where they are without looking at every one of them.
+//
+// SpatialIndex is an R-tree: shapes are grouped into boxes, those boxes
+// into bigger boxes, and so on up to a single root. A query only walks
+// down into boxes that could hold an answer, so with thousands of
+// shapes most of them never get looked at. Each node holds at most
+// MAX_CHILDREN entries; a full node is split in two (Guttman's
+// quadratic split), and a node left with too few after a removal is
+// broken up and its shapes put back in.
+
+use crate::{BoundingBox, Point, ShapeOperation};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const MAX_CHILDREN: usize = 8;
+const MIN_CHILDREN: usize = 3;
+
+// What insert hands back, to get at or remove that shape later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeId(usize);
+
+struct Node {
+    bbox: BoundingBox,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Leaf(Vec<(BoundingBox, ShapeId)>),
+    Branch(Vec<Node>),
+}
+
+impl Node {
+    fn leaf(entries: Vec<(BoundingBox, ShapeId)>) -> Node {
+        let mut node = Node {
+            bbox: empty_box(),
+            kind: NodeKind::Leaf(entries),
+        };
+        node.refit();
+        node
+    }
+
+    fn branch(children: Vec<Node>) -> Node {
+        let mut node = Node {
+            bbox: empty_box(),
+            kind: NodeKind::Branch(children),
+        };
+        node.refit();
+        node
+    }
+
+    fn len(&self) -> usize {
+        match &self.kind {
+            NodeKind::Leaf(entries) => entries.len(),
+            NodeKind::Branch(children) => children.len(),
+        }
+    }
+
+    fn refit(&mut self) {
+        self.bbox = match &self.kind {
+            NodeKind::Leaf(entries) => entries
+                .iter()
+                .fold(empty_box(), |bbox, (entry, _)| bbox.union(entry)),
+            NodeKind::Branch(children) => children
+                .iter()
+                .fold(empty_box(), |bbox, child| bbox.union(&child.bbox)),
+        };
+    }
+
+    // Adds the entry somewhere below this node. If this node ends up too
+    // full it splits, keeps one half and returns the other for the
+    // parent to adopt.
+    fn insert(&mut self, bbox: BoundingBox, id: ShapeId) -> Option<Node> {
+        self.bbox = self.bbox.union(&bbox);
+        match &mut self.kind {
+            NodeKind::Leaf(entries) => {
+                entries.push((bbox, id));
+                if entries.len() <= MAX_CHILDREN {
+                    return None;
+                }
+                let (keep, other) = split(std::mem::take(entries), |(bbox, _)| *bbox);
+                *entries = keep;
+                self.refit();
+                Some(Node::leaf(other))
+            }
+            NodeKind::Branch(children) => {
+                // Whichever child has to grow the least to fit it
+                let best = children
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| {
+                        let cost = |node: &Node| {
+                            let grown = node.bbox.union(&bbox).area() - node.bbox.area();
+                            (grown, node.bbox.area())
+                        };
+                        cost(a).partial_cmp(&cost(b)).unwrap_or(Ordering::Equal)
+                    })
+                    .map(|(i, _)| i)?;
+                let sibling = children[best].insert(bbox, id)?;
+                children.push(sibling);
+                if children.len() <= MAX_CHILDREN {
+                    return None;
+                }
+                let (keep, other) = split(std::mem::take(children), |node| node.bbox);
+                *children = keep;
+                self.refit();
+                Some(Node::branch(other))
+            }
+        }
+    }
+
+    // Takes the entry out if it's below this node. Children that end up
+    // under-full are dropped and their entries added to `orphans`.
+    fn remove(
+        &mut self,
+        bbox: &BoundingBox,
+        id: ShapeId,
+        orphans: &mut Vec<(BoundingBox, ShapeId)>,
+    ) -> bool {
+        if !self.bbox.contains(bbox) {
+            return false;
+        }
+        let found = match &mut self.kind {
+            NodeKind::Leaf(entries) => match entries.iter().position(|(_, e)| *e == id) {
+                Some(i) => {
+                    entries.swap_remove(i);
+                    true
+                }
+                None => false,
+            },
+            NodeKind::Branch(children) => {
+                match children
+                    .iter_mut()
+                    .position(|child| child.remove(bbox, id, orphans))
+                {
+                    Some(i) => {
+                        if children[i].len() < MIN_CHILDREN {
+                            children.swap_remove(i).collect_entries(orphans);
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            }
+        };
+        if found {
+            self.refit();
+        }
+        found
+    }
+
+    fn collect_entries(self, into: &mut Vec<(BoundingBox, ShapeId)>) {
+        match self.kind {
+            NodeKind::Leaf(entries) => into.extend(entries),
+            NodeKind::Branch(children) => {
+                for child in children {
+                    child.collect_entries(into);
+                }
+            }
+        }
+    }
+
+    fn query(&self, region: &BoundingBox, found: &mut Vec<ShapeId>) {
+        if !self.bbox.intersects(region) {
+            return;
+        }
+        match &self.kind {
+            NodeKind::Leaf(entries) => found.extend(
+                entries
+                    .iter()
+                    .filter(|(bbox, _)| bbox.intersects(region))
+                    .map(|(_, id)| *id),
+            ),
+            NodeKind::Branch(children) => {
+                for child in children {
+                    child.query(region, found);
+                }
+            }
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match &self.kind {
+            NodeKind::Leaf(_) => 1,
+            NodeKind::Branch(children) => 1 + children.first().map_or(0, Node::depth),
+        }
+    }
+}
+
+// min at +infinity, max at -infinity: contains nothing, and a union
+// with it changes nothing
+fn empty_box() -> BoundingBox {
+    BoundingBox::around(&[])
+}
+
+// Guttman's quadratic split. Start each half with the two items that
+// would waste the most space sharing a box, then hand out the rest one
+// at a time, most opinionated first, to whichever half grows less.
+fn split<T>(mut items: Vec<T>, bbox_of: impl Fn(&T) -> BoundingBox) -> (Vec<T>, Vec<T>) {
+    let waste = |a: &BoundingBox, b: &BoundingBox| a.union(b).area() - a.area() - b.area();
+    let mut seeds = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+    for i in 0..items.len() {
+        for j in i + 1..items.len() {
+            let wasted = waste(&bbox_of(&items[i]), &bbox_of(&items[j]));
+            if wasted > worst {
+                worst = wasted;
+                seeds = (i, j);
+            }
+        }
+    }
+    // Take the later one out first so the earlier index stays put
+    let second = items.swap_remove(seeds.1);
+    let first = items.swap_remove(seeds.0);
+    let (mut a_box, mut b_box) = (bbox_of(&first), bbox_of(&second));
+    let (mut a, mut b) = (vec![first], vec![second]);
+
+    while !items.is_empty() {
+        // If one half needs everything that's left to reach the minimum,
+        // it gets it
+        if a.len() + items.len() <= MIN_CHILDREN {
+            a.append(&mut items);
+            break;
+        }
+        if b.len() + items.len() <= MIN_CHILDREN {
+            b.append(&mut items);
+            break;
+        }
+        let growth = |item: &T| {
+            let bbox = bbox_of(item);
+            (
+                a_box.union(&bbox).area() - a_box.area(),
+                b_box.union(&bbox).area() - b_box.area(),
+            )
+        };
+        let next = (0..items.len())
+            .max_by(|&i, &j| {
+                let (ai, bi) = growth(&items[i]);
+                let (aj, bj) = growth(&items[j]);
+                (ai - bi).abs().total_cmp(&(aj - bj).abs())
+            })
+            .unwrap_or(0);
+        let item = items.swap_remove(next);
+        let (grow_a, grow_b) = growth(&item);
+        let to_a = match grow_a.total_cmp(&grow_b) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => a.len() <= b.len(),
+        };
+        if to_a {
+            a_box = a_box.union(&bbox_of(&item));
+            a.push(item);
+        } else {
+            b_box = b_box.union(&bbox_of(&item));
+            b.push(item);
+        }
+    }
+    (a, b)
+}
+
+pub struct SpatialIndex<S> {
+    // Indexed by ShapeId; None once removed. Ids aren't reused.
+    shapes: Vec<Option<(S, BoundingBox)>>,
+    root: Node,
+    len: usize,
+}
+
+impl<S: ShapeOperation> SpatialIndex<S> {
+    pub fn new() -> SpatialIndex<S> {
+        SpatialIndex {
+            shapes: Vec::new(),
+            root: Node::leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // The shape's bounding box is worked out once, here. Don't change a
+    // shape's position behind the index's back.
+    pub fn insert(&mut self, shape: S) -> ShapeId {
+        let id = ShapeId(self.shapes.len());
+        let bbox = shape.bounding_box();
+        self.shapes.push(Some((shape, bbox)));
+        self.insert_entry(bbox, id);
+        self.len += 1;
+        id
+    }
+
+    pub fn get(&self, id: ShapeId) -> Option<&S> {
+        self.shapes
+            .get(id.0)
+            .and_then(|slot| slot.as_ref())
+            .map(|(shape, _)| shape)
+    }
+
+    pub fn remove(&mut self, id: ShapeId) -> Option<S> {
+        let (shape, bbox) = self.shapes.get_mut(id.0)?.take()?;
+        let mut orphans = Vec::new();
+        self.root.remove(&bbox, id, &mut orphans);
+        for (bbox, id) in orphans {
+            self.insert_entry(bbox, id);
+        }
+        // A root with a single child is just a longer way to get there
+        while let NodeKind::Branch(children) = &mut self.root.kind {
+            if children.len() != 1 {
+                break;
+            }
+            self.root = children.pop().unwrap();
+        }
+        self.len -= 1;
+        Some(shape)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ShapeId, &S)> {
+        self.shapes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.as_ref().map(|(shape, _)| (ShapeId(i), shape)))
+    }
+
+    // Every shape whose bounding box overlaps the region. Cheap, but a
+    // round or slanted shape can turn up without actually reaching into
+    // the region; use overlapping() to be exact.
+    pub fn query(&self, region: &BoundingBox) -> Vec<ShapeId> {
+        let mut found = Vec::new();
+        self.root.query(region, &mut found);
+        found.sort();
+        found
+    }
+
+    // Every shape that really overlaps `region`, whatever shape that is
+    pub fn overlapping(&self, region: &dyn ShapeOperation) -> Vec<ShapeId> {
+        self.query(&region.bounding_box())
+            .into_iter()
+            .filter(|id| self.get(*id).is_some_and(|shape| shape.intersects(region)))
+            .collect()
+    }
+
+    // The shape closest to the point (0 away if the point is inside it).
+    // Nodes are visited closest box first, and a box can't be closer
+    // than anything in it, so we can stop at the first actual shape that
+    // comes off the queue.
+    pub fn nearest(&self, point: &Point) -> Option<(ShapeId, &S)> {
+        let mut queue = BinaryHeap::new();
+        queue.push(Candidate {
+            distance: self.root.bbox.distance_to(point),
+            item: Item::Node(&self.root),
+        });
+        while let Some(Candidate { item, .. }) = queue.pop() {
+            match item {
+                Item::Shape(id) => return self.get(id).map(|shape| (id, shape)),
+                Item::Node(node) => match &node.kind {
+                    NodeKind::Leaf(entries) => {
+                        for (_, id) in entries {
+                            if let Some(shape) = self.get(*id) {
+                                queue.push(Candidate {
+                                    distance: shape.distance_to(point),
+                                    item: Item::Shape(*id),
+                                });
+                            }
+                        }
+                    }
+                    NodeKind::Branch(children) => {
+                        for child in children {
+                            queue.push(Candidate {
+                                distance: child.bbox.distance_to(point),
+                                item: Item::Node(child),
+                            });
+                        }
+                    }
+                },
+            }
+        }
+        None
+    }
+
+    // How many levels the tree has. Grows with the log of the number of
+    // shapes - handy to check the tree stays balanced.
+    pub fn depth(&self) -> usize {
+        self.root.depth()
+    }
+
+    fn insert_entry(&mut self, bbox: BoundingBox, id: ShapeId) {
+        if let Some(sibling) = self.root.insert(bbox, id) {
+            let old_root = std::mem::replace(&mut self.root, Node::leaf(Vec::new()));
+            self.root = Node::branch(vec![old_root, sibling]);
+        }
+    }
+}
+
+impl<S: ShapeOperation> Default for SpatialIndex<S> {
+    fn default() -> SpatialIndex<S> {
+        SpatialIndex::new()
+    }
+}
+
+impl<S: ShapeOperation> FromIterator<S> for SpatialIndex<S> {
+    fn from_iter<I: IntoIterator<Item = S>>(shapes: I) -> SpatialIndex<S> {
+        let mut index = SpatialIndex::new();
+        for shape in shapes {
+            index.insert(shape);
+        }
+        index
+    }
+}
+
+// Things waiting to be looked at by nearest(), closest first
+enum Item<'a> {
+    Node(&'a Node),
+    Shape(ShapeId),
+}
+
+struct Candidate<'a> {
+    distance: f64,
+    item: Item<'a>,
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// BinaryHeap pops the biggest, so closer has to compare as bigger. On a
+// tie, shapes go before nodes so we can stop as early as possible.
+impl Ord for Candidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let is_shape = |candidate: &Candidate| matches!(candidate.item, Item::Shape(_));
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| is_shape(self).cmp(&is_shape(other)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Circle, Polygon, Shape};
+
+    // A grid of small circles, 10 apart, radius 1
+    fn grid(size: usize) -> Vec<Circle> {
+        (0..size * size)
+            .map(|i| Circle {
+                center: Point::new((i % size) as f64 * 10.0, (i / size) as f64 * 10.0),
+                radius: 1.0,
+            })
+            .collect()
+    }
+
+    fn region(x: f64, y: f64, width: f64, height: f64) -> BoundingBox {
+        BoundingBox::new(Point::new(x, y), Point::new(x + width, y + height))
+    }
+
+    fn linear_scan(shapes: &[Circle], region: &BoundingBox) -> Vec<ShapeId> {
+        shapes
+            .iter()
+            .enumerate()
+            .filter(|(_, shape)| shape.bounding_box().intersects(region))
+            .map(|(i, _)| ShapeId(i))
+            .collect()
+    }
+
+    #[test]
+    fn range_query_matches_a_linear_scan() {
+        let shapes = grid(30);
+        let index: SpatialIndex<Circle> = shapes.iter().cloned().collect();
+        assert_eq!(900, index.len());
+        // Roughly log8(900) levels, not hundreds
+        assert!(index.depth() <= 5, "depth {}", index.depth());
+
+        for area in [
+            region(-5.0, -5.0, 12.0, 12.0),
+            region(45.0, 45.0, 30.0, 5.0),
+            region(0.0, 0.0, 290.0, 290.0),
+            region(500.0, 500.0, 10.0, 10.0),
+        ] {
+            assert_eq!(linear_scan(&shapes, &area), index.query(&area));
+        }
+    }
+
+    #[test]
+    fn overlapping_is_exact() {
+        let index: SpatialIndex<Circle> = grid(3).into_iter().collect();
+        // Reaches into the bounding box of the circle at (0, 0) but
+        // misses the circle itself
+        let corner = Polygon::new(vec![
+            Point::new(0.8, 0.8),
+            Point::new(2.0, 0.8),
+            Point::new(2.0, 2.0),
+            Point::new(0.8, 2.0),
+        ]);
+        assert_eq!(vec![ShapeId(0)], index.query(&corner.bounding_box()));
+        assert!(index.overlapping(&corner).is_empty());
+        let wide = Circle {
+            center: Point::new(15.0, 0.0),
+            radius: 5.0,
+        };
+        assert_eq!(vec![ShapeId(1), ShapeId(2)], index.overlapping(&wide));
+    }
+
+    #[test]
+    fn remove_takes_shapes_out_of_queries() {
+        let shapes = grid(20);
+        let mut index: SpatialIndex<Circle> = shapes.iter().cloned().collect();
+        let everything = region(-10.0, -10.0, 300.0, 300.0);
+
+        // Take out every other shape
+        for i in (0..400).step_by(2) {
+            assert_eq!(Some(shapes[i].clone()), index.remove(ShapeId(i)));
+        }
+        assert_eq!(None, index.remove(ShapeId(0)));
+        assert_eq!(200, index.len());
+        let odd: Vec<ShapeId> = (1..400).step_by(2).map(ShapeId).collect();
+        assert_eq!(odd, index.query(&everything));
+        assert_eq!(odd, index.iter().map(|(id, _)| id).collect::<Vec<_>>());
+
+        for id in odd {
+            index.remove(id);
+        }
+        assert!(index.is_empty());
+        assert!(index.query(&everything).is_empty());
+        assert_eq!(1, index.depth());
+    }
+
+    #[test]
+    fn nearest_finds_the_closest_shape() {
+        let index: SpatialIndex<Circle> = grid(10).into_iter().collect();
+        let (id, circle) = index.nearest(&Point::new(42.0, 68.0)).unwrap();
+        assert_eq!(ShapeId(74), id);
+        assert_eq!(Point::new(40.0, 70.0), circle.center);
+        assert_eq!(
+            ShapeId(0),
+            index.nearest(&Point::new(-100.0, -3.0)).unwrap().0
+        );
+        assert!(SpatialIndex::<Circle>::new()
+            .nearest(&Point::new(0.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn nearest_uses_the_real_shape_not_its_box() {
+        // The point is inside the big circle's bounding box but closer
+        // to the edge of the small one
+        let mut index = SpatialIndex::new();
+        let big = index.insert(Shape::from(Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 10.0,
+        }));
+        let small = index.insert(Shape::from(Circle {
+            center: Point::new(11.0, 11.0),
+            radius: 2.0,
+        }));
+        let point = Point::new(9.5, 9.5);
+        assert!(index.get(big).unwrap().bounding_box().distance_to(&point) == 0.0);
+        assert_eq!(small, index.nearest(&point).unwrap().0);
+    }
+
+    #[test]
+    fn boxed_shapes_can_be_indexed() {
+        let mut index: SpatialIndex<Box<dyn ShapeOperation>> = SpatialIndex::new();
+        index.insert(Box::new(Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        }));
+        index.insert(Box::new(crate::Rectangle {
+            width: 2.0,
+            height: 2.0,
+        }));
+        assert_eq!(2, index.query(&region(0.5, 0.5, 0.1, 0.1)).len());
+    }
+}
@@ -0,0 +1,259 @@
+// Drawing a collection of shapes as an SVG picture. Any mix of shapes
+// works since all we need from each one is what ShapeOperation gives
+// us: its bounding box and its svg_element().
+//
+// Shapes use the usual maths convention of y pointing up, SVG has y
+// pointing down, so the whole picture is flipped on the way out.
+
+use crate::{BoundingBox, Point, ShapeOperation};
+use std::{fs, io, path::Path};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    pub fill: String,
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub opacity: f64,
+}
+
+impl Style {
+    pub fn new(fill: &str, stroke: &str) -> Style {
+        Style {
+            fill: fill.to_string(),
+            stroke: stroke.to_string(),
+            ..Style::default()
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            fill: String::from("none"),
+            stroke: String::from("black"),
+            stroke_width: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    // Draw every shape where its coordinates say it is
+    AsIs,
+    // Line the shapes up left to right, `gap` apart. Handy for Rectangle
+    // and Triangle, which would otherwise all pile up on the origin.
+    Row { gap: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    // Shape n is drawn with styles[n % styles.len()]
+    pub styles: Vec<Style>,
+    pub layout: Layout,
+    // Empty space around the drawing
+    pub margin: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            styles: vec![
+                Style::new("#f4a261", "#264653"),
+                Style::new("#2a9d8f", "#264653"),
+                Style::new("#e9c46a", "#264653"),
+                Style::new("#e76f51", "#264653"),
+            ],
+            layout: Layout::AsIs,
+            margin: 10.0,
+        }
+    }
+}
+
+pub fn render(shapes: &[Box<dyn ShapeOperation>], options: &SvgOptions) -> String {
+    // Work out where each shape goes before drawing anything, since the
+    // size of the picture depends on all of them
+    let mut cursor = 0.0;
+    let placed: Vec<(Point, BoundingBox)> = shapes
+        .iter()
+        .map(|shape| {
+            let bbox = shape.bounding_box();
+            let offset = match options.layout {
+                Layout::AsIs => Point::new(0.0, 0.0),
+                Layout::Row { gap } => {
+                    let offset = Point::new(cursor - bbox.min.x, 0.0 - bbox.min.y);
+                    cursor += bbox.width() + gap;
+                    offset
+                }
+            };
+            let moved = BoundingBox::new(
+                Point::new(bbox.min.x + offset.x, bbox.min.y + offset.y),
+                Point::new(bbox.max.x + offset.x, bbox.max.y + offset.y),
+            );
+            (offset, moved)
+        })
+        .collect();
+
+    let bounds = placed
+        .iter()
+        .map(|(_, bbox)| *bbox)
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or(BoundingBox::new(Point::new(0.0, 0.0), Point::new(0.0, 0.0)));
+    let margin = options.margin;
+    let width = bounds.width() + 2.0 * margin;
+    let height = bounds.height() + 2.0 * margin;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+    svg += &format!(
+        "  <g transform=\"translate({} {}) scale(1 -1)\">\n",
+        margin - bounds.min.x,
+        margin + bounds.max.y
+    );
+    for (i, (shape, (offset, _))) in shapes.iter().zip(&placed).enumerate() {
+        let default_style = Style::default();
+        let style = if options.styles.is_empty() {
+            &default_style
+        } else {
+            &options.styles[i % options.styles.len()]
+        };
+        svg += &format!(
+            "    <g transform=\"translate({} {})\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\" opacity=\"{}\">{}</g>\n",
+            offset.x,
+            offset.y,
+            escape(&style.fill),
+            escape(&style.stroke),
+            style.stroke_width,
+            style.opacity,
+            shape.svg_element()
+        );
+    }
+    svg += "  </g>\n</svg>\n";
+    svg
+}
+
+pub fn export(
+    path: impl AsRef<Path>,
+    shapes: &[Box<dyn ShapeOperation>],
+    options: &SvgOptions,
+) -> io::Result<()> {
+    fs::write(path, render(shapes, options))
+}
+
+pub(crate) fn rect_element(min: Point, width: f64, height: f64) -> String {
+    format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+        min.x, min.y, width, height
+    )
+}
+
+pub(crate) fn polygon_element(points: &[Point]) -> String {
+    let points: Vec<String> = points
+        .iter()
+        .map(|point| format!("{},{}", point.x, point.y))
+        .collect();
+    format!(r#"<polygon points="{}"/>"#, points.join(" "))
+}
+
+// Style values end up inside attributes, so keep them from breaking out
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Circle, Ellipse, Polygon, Rectangle, Triangle};
+    use std::env;
+
+    fn shapes() -> Vec<Box<dyn ShapeOperation>> {
+        vec![
+            Box::new(Rectangle {
+                width: 4.0,
+                height: 3.0,
+            }),
+            Box::new(Triangle {
+                base: 6.0,
+                height: 4.0,
+            }),
+            Box::new(Circle {
+                center: Point::new(20.0, 2.0),
+                radius: 2.0,
+            }),
+            Box::new(Ellipse {
+                center: Point::new(0.0, 0.0),
+                radius_x: 3.0,
+                radius_y: 1.0,
+                angle: 0.0,
+            }),
+            Box::new(Polygon::new(vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(0.0, 1.0),
+            ])),
+        ]
+    }
+
+    #[test]
+    fn every_shape_gets_its_own_element() {
+        let svg = render(&shapes(), &SvgOptions::default());
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains(r#"<rect x="0" y="0" width="4" height="3"/>"#));
+        assert!(svg.contains(r#"<polygon points="0,0 6,0 3,4"/>"#));
+        assert!(svg.contains(r#"<circle cx="20" cy="2" r="2"/>"#));
+        assert!(svg.contains(r#"<ellipse cx="0" cy="0" rx="3" ry="1"/>"#));
+        assert!(svg.contains(r#"<polygon points="0,0 1,0 0,1"/>"#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn picture_covers_every_shape_plus_margin() {
+        // x runs from -3 (ellipse) to 22 (circle), y from -1 to 4
+        let svg = render(&shapes(), &SvgOptions::default());
+        assert!(svg.contains(r#"viewBox="0 0 45 25""#));
+        assert!(svg.contains(r#"<g transform="translate(13 14) scale(1 -1)">"#));
+    }
+
+    #[test]
+    fn row_layout_puts_shapes_side_by_side() {
+        let options = SvgOptions {
+            layout: Layout::Row { gap: 1.0 },
+            margin: 0.0,
+            ..SvgOptions::default()
+        };
+        let svg = render(&shapes()[..2], &options);
+        // 4 wide, a gap of 1, then 6 wide
+        assert!(svg.contains(r#"viewBox="0 0 11 4""#));
+        assert!(svg.contains(r#"<g transform="translate(5 0)""#));
+    }
+
+    #[test]
+    fn styles_cycle_and_are_escaped() {
+        let options = SvgOptions {
+            styles: vec![Style::new("red", "blue"), Style::new("\"><script>", "none")],
+            ..SvgOptions::default()
+        };
+        let svg = render(&shapes(), &options);
+        assert_eq!(3, svg.matches(r#"fill="red""#).count());
+        assert!(!svg.contains("<script>"));
+        assert!(svg.contains("&quot;&gt;&lt;script&gt;"));
+    }
+
+    #[test]
+    fn export_writes_a_file() {
+        let path = env::temp_dir().join(format!("cool_lib_shapes_{}.svg", std::process::id()));
+        export(&path, &shapes(), &SvgOptions::default()).unwrap();
+        assert_eq!(
+            render(&shapes(), &SvgOptions::default()),
+            fs::read_to_string(&path).unwrap()
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,297 @@
+// Putting conversations back together from a pile of tweets.
+//
+// Every reply points at its parent, so the replies to one tweet, their
+// replies and so on form a tree. A tweet with no parent (or whose
+// parent we never saw) starts a new thread. Retweets aren't part of the
+// conversation; they're just counted against the tweet they pass on.
+
+use crate::{render, Summary, SummaryParts, Tweet, TweetId};
+use std::collections::{BTreeMap, HashSet};
+
+pub struct Conversations {
+    // BTreeMap keeps everything in id order, which is also time order
+    tweets: BTreeMap<TweetId, Tweet>,
+    replies: BTreeMap<TweetId, Vec<TweetId>>,
+    retweets: BTreeMap<TweetId, usize>,
+    roots: Vec<TweetId>,
+}
+
+impl Conversations {
+    pub fn build(tweets: impl IntoIterator<Item = Tweet>) -> Conversations {
+        let mut all = BTreeMap::new();
+        let mut retweeted = Vec::new();
+        for tweet in tweets {
+            match tweet.retweet_of {
+                Some(original) => retweeted.push(original),
+                None => {
+                    all.insert(tweet.id, tweet);
+                }
+            }
+        }
+        let mut conversations = Conversations {
+            replies: BTreeMap::new(),
+            retweets: BTreeMap::new(),
+            roots: Vec::new(),
+            tweets: all,
+        };
+
+        for original in retweeted {
+            *conversations.retweets.entry(original).or_insert(0) += 1;
+        }
+        for tweet in conversations.tweets.values() {
+            match tweet.in_reply_to {
+                Some(parent)
+                    if parent != tweet.id && conversations.tweets.contains_key(&parent) =>
+                {
+                    conversations
+                        .replies
+                        .entry(parent)
+                        .or_default()
+                        .push(tweet.id)
+                }
+                _ => conversations.roots.push(tweet.id),
+            }
+        }
+
+        // Replies that go round in a circle never lead back to a root.
+        // Whatever's left over once every thread has been walked is in
+        // such a loop; let its earliest tweet start a thread of its own.
+        let mut seen = HashSet::new();
+        for root in &conversations.roots {
+            conversations.walk(*root, 0, &mut seen, &mut |_, _| {});
+        }
+        let ids: Vec<TweetId> = conversations.tweets.keys().copied().collect();
+        for id in ids {
+            if !seen.contains(&id) {
+                conversations.roots.push(id);
+                conversations.walk(id, 0, &mut seen, &mut |_, _| {});
+            }
+        }
+        conversations.roots.sort();
+        conversations
+    }
+
+    pub fn get(&self, id: TweetId) -> Option<&Tweet> {
+        self.tweets.get(&id)
+    }
+
+    // Direct replies only, earliest first
+    pub fn replies(&self, id: TweetId) -> &[TweetId] {
+        self.replies.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn retweet_count(&self, id: TweetId) -> usize {
+        self.retweets.get(&id).copied().unwrap_or(0)
+    }
+
+    // Every thread, oldest first
+    pub fn threads(&self) -> impl Iterator<Item = Thread<'_>> {
+        self.roots.iter().map(|root| Thread {
+            conversations: self,
+            root: *root,
+        })
+    }
+
+    // The whole thread a tweet belongs to, however deep down it is
+    pub fn thread_of(&self, id: TweetId) -> Option<Thread<'_>> {
+        self.threads()
+            .find(|thread| thread.tweets().iter().any(|(_, tweet)| tweet.id == id))
+    }
+
+    // Depth first, replies in id order. `seen` stops us going round a
+    // loop of replies forever.
+    fn walk<'a>(
+        &'a self,
+        id: TweetId,
+        depth: usize,
+        seen: &mut HashSet<TweetId>,
+        visit: &mut dyn FnMut(usize, &'a Tweet),
+    ) {
+        if !seen.insert(id) {
+            return;
+        }
+        if let Some(tweet) = self.tweets.get(&id) {
+            visit(depth, tweet);
+        }
+        for reply in self.replies(id) {
+            self.walk(*reply, depth + 1, seen, visit);
+        }
+    }
+}
+
+// One conversation: a tweet and every reply below it
+pub struct Thread<'a> {
+    conversations: &'a Conversations,
+    root: TweetId,
+}
+
+impl<'a> Thread<'a> {
+    pub fn root(&self) -> &'a Tweet {
+        &self.conversations.tweets[&self.root]
+    }
+
+    // Every tweet in the thread with how deep it sits (the root is 0),
+    // in reading order
+    pub fn tweets(&self) -> Vec<(usize, &'a Tweet)> {
+        let mut tweets = Vec::new();
+        self.conversations
+            .walk(self.root, 0, &mut HashSet::new(), &mut |depth, tweet| {
+                tweets.push((depth, tweet))
+            });
+        tweets
+    }
+
+    pub fn reply_count(&self) -> usize {
+        self.tweets().len() - 1
+    }
+
+    // Retweets of any tweet in the thread
+    pub fn retweet_count(&self) -> usize {
+        self.tweets()
+            .iter()
+            .map(|(_, tweet)| self.conversations.retweet_count(tweet.id))
+            .sum()
+    }
+
+    // Everyone who took part, in order of their first tweet
+    pub fn participants(&self) -> Vec<&'a str> {
+        let mut participants: Vec<&str> = Vec::new();
+        for (_, tweet) in self.tweets() {
+            if !participants.contains(&tweet.username.as_str()) {
+                participants.push(&tweet.username);
+            }
+        }
+        participants
+    }
+}
+
+fn count(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+// A thread is a summary too, so it can go through the same renderers as
+// a single tweet or article
+impl Summary for Thread<'_> {
+    fn summarize(&self) -> String {
+        format!(
+            "{} [{}, {}]",
+            self.root().summarize(),
+            count(self.reply_count(), "reply", "replies"),
+            count(self.retweet_count(), "retweet", "retweets")
+        )
+    }
+    // The title is the opening tweet, cut short; the body is the
+    // whole conversation with each reply indented under its parent
+    fn parts(&self) -> SummaryParts {
+        let lines: Vec<String> = self
+            .tweets()
+            .iter()
+            .map(|(depth, tweet)| format!("{}{}", "  ".repeat(*depth), tweet.summarize()))
+            .collect();
+        SummaryParts {
+            title: Some(render::truncate(&self.root().content, 50)),
+            author: Some(self.participants().join(", ")),
+            location: None,
+            body: lines.join("\n"),
+        }
+    }
+    fn summarize_author(&self) -> String {
+        self.root().username.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlainText;
+
+    // Two threads. The first:
+    //   1 alice
+    //     2 bob
+    //       4 alice
+    //     3 carol
+    // plus 5, a reply to a tweet we never saw, and retweets of 1 and 4.
+    fn tweets() -> Vec<Tweet> {
+        vec![
+            Tweet::new(4, "alice", "Twice as fast").replying_to(2),
+            Tweet::new(1, "alice", "Rust 2.0 is out"),
+            Tweet::new(2, "bob", "What changed?").replying_to(1),
+            Tweet::new(3, "carol", "Finally!").replying_to(1),
+            Tweet::new(5, "dave", "Late to the party").replying_to(99),
+            Tweet::new(6, "erin", "").retweeting(1),
+            Tweet::new(7, "frank", "").retweeting(1),
+            Tweet::new(8, "grace", "").retweeting(4),
+        ]
+    }
+
+    #[test]
+    fn rebuilds_reply_trees() {
+        let conversations = Conversations::build(tweets());
+        let threads: Vec<Thread> = conversations.threads().collect();
+        assert_eq!(2, threads.len());
+        assert_eq!(1, threads[0].root().id);
+        assert_eq!(5, threads[1].root().id);
+
+        let shape: Vec<(usize, TweetId)> = threads[0]
+            .tweets()
+            .iter()
+            .map(|(depth, tweet)| (*depth, tweet.id))
+            .collect();
+        assert_eq!(vec![(0, 1), (1, 2), (2, 4), (1, 3)], shape);
+        assert_eq!(&[2, 3], conversations.replies(1));
+        assert!(conversations.get(6).is_none(), "retweets aren't replies");
+        assert_eq!(vec!["alice", "bob", "carol"], threads[0].participants());
+        assert_eq!(1, conversations.thread_of(4).unwrap().root().id);
+    }
+
+    #[test]
+    fn counts_retweets() {
+        let conversations = Conversations::build(tweets());
+        assert_eq!(2, conversations.retweet_count(1));
+        assert_eq!(1, conversations.retweet_count(4));
+        assert_eq!(0, conversations.retweet_count(2));
+        let thread = conversations.thread_of(1).unwrap();
+        assert_eq!(3, thread.reply_count());
+        assert_eq!(3, thread.retweet_count());
+    }
+
+    #[test]
+    fn summarises_a_thread() {
+        let conversations = Conversations::build(tweets());
+        let thread = conversations.thread_of(1).unwrap();
+        assert_eq!(
+            "alice: Rust 2.0 is out [3 replies, 3 retweets]",
+            thread.summarize()
+        );
+        assert_eq!("alice", thread.summarize_author());
+        assert_eq!(
+            "Rust 2.0 is out\n\
+             by alice, bob, carol\n\
+             alice: Rust 2.0 is out\n  \
+             bob: What changed?\n    \
+             alice: Twice as fast\n  \
+             carol: Finally!",
+            thread.render(&PlainText)
+        );
+        let lonely = conversations.thread_of(5).unwrap();
+        assert_eq!(
+            "dave: Late to the party [0 replies, 0 retweets]",
+            lonely.summarize()
+        );
+    }
+
+    #[test]
+    fn reply_loops_do_not_hang() {
+        let conversations = Conversations::build(vec![
+            Tweet::new(1, "a", "first").replying_to(2),
+            Tweet::new(2, "b", "second").replying_to(1),
+            Tweet::new(3, "c", "third").replying_to(2),
+            Tweet::new(4, "d", "talking to myself").replying_to(4),
+        ]);
+        let threads: Vec<Thread> = conversations.threads().collect();
+        assert_eq!(2, threads.len());
+        assert_eq!(1, threads[0].root().id);
+        assert_eq!(3, threads[0].tweets().len());
+        assert_eq!(4, threads[1].root().id);
+    }
+}
@@ -0,0 +1,437 @@
+// Moving, turning and resizing shapes.
+//
+// A Transform is a 2D affine map: every point (x, y) goes to
+//
+//     x' = a * x + c * y + e
+//     y' = b * x + d * y + f
+//
+// (the same six numbers, in the same order, as SVG's matrix()). That
+// covers translating, rotating, scaling, mirroring and shearing, and
+// chaining any number of them still gives a single Transform.
+//
+// Rectangle and Triangle live at the origin and can't be turned, so
+// they come out the other end as Polygons. Ellipses stay ellipses and
+// circles stay circles as long as they aren't stretched out of shape.
+
+use crate::{Circle, Ellipse, Point, Polygon, Rectangle, Shape, ShapeOperation, Triangle};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform::scale(1.0, 1.0)
+    }
+
+    pub fn translate(dx: f64, dy: f64) -> Transform {
+        Transform {
+            e: dx,
+            f: dy,
+            ..Transform::identity()
+        }
+    }
+
+    // Anticlockwise about the origin, in radians
+    pub fn rotate(angle: f64) -> Transform {
+        let (sin, cos) = angle.sin_cos();
+        Transform {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    // Anticlockwise about any point: move it to the origin, turn, and
+    // move it back
+    pub fn rotate_about(angle: f64, center: &Point) -> Transform {
+        Transform::translate(-center.x, -center.y)
+            .then(&Transform::rotate(angle))
+            .then(&Transform::translate(center.x, center.y))
+    }
+
+    // Away from the origin. A negative factor mirrors.
+    pub fn scale(sx: f64, sy: f64) -> Transform {
+        Transform {
+            a: sx,
+            b: 0.0,
+            c: 0.0,
+            d: sy,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    // This transform followed by `next`
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    pub fn apply(&self, point: &Point) -> Point {
+        Point::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    // How much areas grow by. Negative when the transform mirrors.
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    // Where the ellipse `center + M * (unit circle)` ends up, with M the
+    // 2x2 matrix [[m11, m12], [m21, m22]]. Its new radii and angle are
+    // the singular values and the turn of (our matrix) * M.
+    fn ellipse(&self, center: &Point, m: [[f64; 2]; 2]) -> Ellipse {
+        let p = self.a * m[0][0] + self.c * m[1][0];
+        let q = self.a * m[0][1] + self.c * m[1][1];
+        let r = self.b * m[0][0] + self.d * m[1][0];
+        let s = self.b * m[0][1] + self.d * m[1][1];
+        // Closed form singular value decomposition of [[p, q], [r, s]]
+        let (e, f, g, h) = ((p + s) / 2.0, (p - s) / 2.0, (r + q) / 2.0, (r - q) / 2.0);
+        let (big, small) = (e.hypot(h), f.hypot(g));
+        Ellipse {
+            center: self.apply(center),
+            radius_x: big + small,
+            radius_y: (big - small).abs(),
+            angle: (h.atan2(e) + g.atan2(f)) / 2.0,
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::identity()
+    }
+}
+
+pub trait Transformable {
+    // Whatever kind of shape can hold the result
+    type Output: ShapeOperation;
+
+    fn transformed(&self, transform: &Transform) -> Self::Output;
+
+    // Shortcuts for the common cases
+    fn translated(&self, dx: f64, dy: f64) -> Self::Output {
+        self.transformed(&Transform::translate(dx, dy))
+    }
+    fn rotated(&self, angle: f64) -> Self::Output {
+        self.transformed(&Transform::rotate(angle))
+    }
+    fn scaled(&self, sx: f64, sy: f64) -> Self::Output {
+        self.transformed(&Transform::scale(sx, sy))
+    }
+}
+
+impl Transformable for Rectangle {
+    type Output = Polygon;
+
+    fn transformed(&self, transform: &Transform) -> Polygon {
+        Polygon::new(self.vertices().to_vec()).transformed(transform)
+    }
+}
+
+impl Transformable for Triangle {
+    type Output = Polygon;
+
+    fn transformed(&self, transform: &Transform) -> Polygon {
+        Polygon::new(self.vertices().to_vec()).transformed(transform)
+    }
+}
+
+impl Transformable for Polygon {
+    type Output = Polygon;
+
+    fn transformed(&self, transform: &Transform) -> Polygon {
+        Polygon::new(self.vertices.iter().map(|v| transform.apply(v)).collect())
+    }
+}
+
+impl Transformable for Ellipse {
+    type Output = Ellipse;
+
+    fn transformed(&self, transform: &Transform) -> Ellipse {
+        let (sin, cos) = self.angle.sin_cos();
+        transform.ellipse(
+            &self.center,
+            [
+                [self.radius_x * cos, -self.radius_y * sin],
+                [self.radius_x * sin, self.radius_y * cos],
+            ],
+        )
+    }
+}
+
+// A circle stretched more one way than the other is an ellipse, so this
+// hands back a Shape
+impl Transformable for Circle {
+    type Output = Shape;
+
+    fn transformed(&self, transform: &Transform) -> Shape {
+        let ellipse = transform.ellipse(&self.center, [[self.radius, 0.0], [0.0, self.radius]]);
+        if (ellipse.radius_x - ellipse.radius_y).abs() <= 1e-9 * ellipse.radius_x {
+            Shape::Circle(Circle {
+                center: ellipse.center,
+                radius: ellipse.radius_x,
+            })
+        } else {
+            Shape::Ellipse(ellipse)
+        }
+    }
+}
+
+impl Transformable for Shape {
+    type Output = Shape;
+
+    fn transformed(&self, transform: &Transform) -> Shape {
+        match self {
+            Shape::Rectangle(shape) => shape.transformed(transform).into(),
+            Shape::Triangle(shape) => shape.transformed(transform).into(),
+            Shape::Circle(shape) => shape.transformed(transform),
+            Shape::Ellipse(shape) => shape.transformed(transform).into(),
+            Shape::Polygon(shape) => shape.transformed(transform).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_close, close};
+    use proptest::prelude::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    // One of everything, none of them at the origin or lined up with
+    // the axes in any special way
+    fn shapes() -> Vec<Shape> {
+        vec![
+            Rectangle {
+                width: 4.0,
+                height: 3.0,
+            }
+            .into(),
+            Triangle {
+                base: 6.0,
+                height: 4.0,
+            }
+            .into(),
+            Circle {
+                center: Point::new(1.0, -2.0),
+                radius: 2.5,
+            }
+            .into(),
+            Ellipse {
+                center: Point::new(-3.0, 1.0),
+                radius_x: 3.0,
+                radius_y: 1.0,
+                angle: 0.4,
+            }
+            .into(),
+            Polygon::new(vec![
+                Point::new(0.0, 0.0),
+                Point::new(2.0, 0.0),
+                Point::new(2.0, 1.0),
+                Point::new(1.0, 1.0),
+                Point::new(1.0, 2.0),
+                Point::new(0.0, 2.0),
+            ])
+            .into(),
+        ]
+    }
+
+    #[test]
+    fn basic_transforms_move_points() {
+        let point = Point::new(2.0, 1.0);
+        assert_eq!(point, Transform::identity().apply(&point));
+        assert_eq!(
+            Point::new(5.0, -1.0),
+            Transform::translate(3.0, -2.0).apply(&point)
+        );
+        assert_eq!(
+            Point::new(4.0, -3.0),
+            Transform::scale(2.0, -3.0).apply(&point)
+        );
+        let turned = Transform::rotate(FRAC_PI_2).apply(&point);
+        assert_close(-1.0, turned.x);
+        assert_close(2.0, turned.y);
+        let about = Transform::rotate_about(PI, &Point::new(1.0, 1.0)).apply(&point);
+        assert_close(0.0, about.x);
+        assert_close(1.0, about.y);
+    }
+
+    #[test]
+    fn then_applies_in_order() {
+        let point = Point::new(1.0, 0.0);
+        // Move first and then double: (1 + 1) * 2
+        let moved_then_scaled = Transform::translate(1.0, 0.0).then(&Transform::scale(2.0, 2.0));
+        assert_eq!(Point::new(4.0, 0.0), moved_then_scaled.apply(&point));
+        // Double first and then move: 1 * 2 + 1
+        let scaled_then_moved = Transform::scale(2.0, 2.0).then(&Transform::translate(1.0, 0.0));
+        assert_eq!(Point::new(3.0, 0.0), scaled_then_moved.apply(&point));
+    }
+
+    #[test]
+    fn rectangle_becomes_a_turned_polygon() {
+        let rectangle = Rectangle {
+            width: 4.0,
+            height: 2.0,
+        };
+        let turned = rectangle.rotated(FRAC_PI_2).translated(10.0, 0.0);
+        assert_close(8.0, turned.area());
+        let bbox = turned.bounding_box();
+        assert_close(8.0, bbox.min.x);
+        assert_close(10.0, bbox.max.x);
+        assert_close(4.0, bbox.max.y);
+    }
+
+    #[test]
+    fn circles_stay_round_unless_stretched() {
+        let circle = Circle {
+            center: Point::new(1.0, 1.0),
+            radius: 1.0,
+        };
+        match circle.rotated(1.0).translated(2.0, 0.0) {
+            Shape::Circle(moved) => assert_close(1.0, moved.radius),
+            other => panic!("expected a circle, got {:?}", other),
+        }
+        match circle.scaled(3.0, 1.0) {
+            Shape::Ellipse(stretched) => {
+                assert_eq!(Point::new(3.0, 1.0), stretched.center);
+                assert_close(3.0, stretched.radius_x);
+                assert_close(1.0, stretched.radius_y);
+                assert_close(0.0, stretched.angle.sin());
+            }
+            other => panic!("expected an ellipse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn turned_ellipse_has_a_wider_bounding_box() {
+        let ellipse = Ellipse {
+            center: Point::new(0.0, 0.0),
+            radius_x: 2.0,
+            radius_y: 1.0,
+            angle: 0.0,
+        };
+        let turned = ellipse.rotated(FRAC_PI_2);
+        let bbox = turned.bounding_box();
+        assert_close(1.0, bbox.max.x);
+        assert_close(2.0, bbox.max.y);
+        assert!(turned.contains_point(&Point::new(0.0, 1.9)));
+        assert!(!turned.contains_point(&Point::new(1.9, 0.0)));
+    }
+
+    fn transform() -> impl Strategy<Value = Transform> {
+        let step = prop_oneof![
+            (-100.0..100.0, -100.0..100.0).prop_map(|(dx, dy)| Transform::translate(dx, dy)),
+            (-10.0..10.0).prop_map(Transform::rotate),
+            (0.1..10.0f64, 0.1..10.0f64, any::<bool>()).prop_map(|(sx, sy, mirror)| {
+                Transform::scale(if mirror { -sx } else { sx }, sy)
+            }),
+        ];
+        prop::collection::vec(step, 1..5).prop_map(|steps| {
+            steps
+                .iter()
+                .fold(Transform::identity(), |total, step| total.then(step))
+        })
+    }
+
+    // Only moves and turns, which keep every length as it was
+    fn rigid() -> impl Strategy<Value = Transform> {
+        (-100.0..100.0, -100.0..100.0, -10.0..10.0).prop_map(|(dx, dy, angle)| {
+            Transform::rotate(angle).then(&Transform::translate(dx, dy))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn area_grows_by_the_determinant(transform in transform()) {
+            for shape in shapes() {
+                let moved = shape.transformed(&transform);
+                let expected = shape.area() * transform.determinant().abs();
+                prop_assert!(close(expected, moved.area()), "{:?} -> {:?}", shape, moved);
+            }
+        }
+
+        #[test]
+        fn moving_and_turning_keeps_perimeter_and_area(transform in rigid()) {
+            for shape in shapes() {
+                let moved = shape.transformed(&transform);
+                prop_assert!(close(shape.area(), moved.area()));
+                prop_assert!(close(shape.perimeter(), moved.perimeter()), "{:?} -> {:?}", shape, moved);
+            }
+        }
+
+        #[test]
+        fn uniform_scaling_scales_the_perimeter(
+            transform in rigid(),
+            factor in 0.1..10.0f64,
+        ) {
+            let transform = transform.then(&Transform::scale(factor, factor));
+            for shape in shapes() {
+                let moved = shape.transformed(&transform);
+                prop_assert!(close(shape.perimeter() * factor, moved.perimeter()));
+                prop_assert!(close(shape.area() * factor * factor, moved.area()));
+            }
+        }
+
+        #[test]
+        fn transformed_ellipse_goes_through_the_moved_edge(
+            transform in transform(),
+            turn in 0.0..std::f64::consts::TAU,
+        ) {
+            let ellipse = Ellipse {
+                center: Point::new(-3.0, 1.0),
+                radius_x: 3.0,
+                radius_y: 1.0,
+                angle: 0.4,
+            };
+            // A point on the edge of the original...
+            let (sin, cos) = ellipse.angle.sin_cos();
+            let (x, y) = (ellipse.radius_x * turn.cos(), ellipse.radius_y * turn.sin());
+            let edge = Point::new(
+                ellipse.center.x + x * cos - y * sin,
+                ellipse.center.y + x * sin + y * cos,
+            );
+            // ...lands on the edge of the transformed one
+            let moved = ellipse.transformed(&transform);
+            let point = transform.apply(&edge);
+            let (sin, cos) = moved.angle.sin_cos();
+            let (dx, dy) = (point.x - moved.center.x, point.y - moved.center.y);
+            let (u, v) = (
+                (dx * cos + dy * sin) / moved.radius_x,
+                (dy * cos - dx * sin) / moved.radius_y,
+            );
+            prop_assert!(close(1.0, u * u + v * v), "{:?}", moved);
+        }
+
+        #[test]
+        fn then_matches_applying_one_after_the_other(
+            first in transform(),
+            second in transform(),
+            x in -100.0..100.0f64,
+            y in -100.0..100.0f64,
+        ) {
+            let point = Point::new(x, y);
+            let together = first.then(&second).apply(&point);
+            let one_by_one = second.apply(&first.apply(&point));
+            prop_assert!(together.distance(&one_by_one) <= 1e-6 * one_by_one.x.hypot(one_by_one.y).max(1.0));
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// #[derive(Summary)] - writes the summarize() we'd otherwise write by
+// hand with a format!.
+//
+// With no attributes the summary is every field, in the order they're
+// declared, separated by commas:
+//
+//     #[derive(Summary)]
+//     struct Song {
+//         title: String,
+//         #[summary(skip)]
+//         plays: u64,
+//         #[summary(order = 1)]
+//         artist: String,
+//     }
+//
+// gives "Zappa, Peaches en Regalia": fields with an `order` come first,
+// lowest first, then the rest. Skipped fields are left out.
+//
+// For anything fancier, give the struct a template. Fields go in braces
+// by name, the same as in format!:
+//
+//     #[derive(Summary)]
+//     #[summary(template = "{headline}, by {author}")]
+//     struct Story { headline: String, author: String }
+//
+// A template naming a field the struct doesn't have (or one marked
+// skip) won't compile.
+//
+// Every field that ends up in the summary has to implement Display.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitInt, LitStr, Result};
+
+#[proc_macro_derive(Summary, attributes(summary))]
+pub fn derive_summary(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Field {
+    name: Ident,
+    order: Option<i64>,
+    skip: bool,
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let fields = fields(input)?;
+    let body = match template(input)? {
+        Some(template) => with_template(&template, &fields, &input.ident)?,
+        None => in_order(&fields),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    // The path assumes the trait's crate is called cool_lib wherever the
+    // derive is used, cool_lib itself included (see its lib.rs)
+    Ok(quote! {
+        impl #impl_generics ::cool_lib::Summary for #name #type_generics #where_clause {
+            fn summarize(&self) -> ::std::string::String {
+                #body
+            }
+        }
+    })
+}
+
+// The struct's fields with whatever #[summary(...)] says about them
+fn fields(input: &DeriveInput) -> Result<Vec<Field>> {
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            // Nothing to call a tuple struct's fields by in a template
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "#[derive(Summary)] needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[derive(Summary)] only works on structs",
+            ))
+        }
+    };
+
+    let mut fields = Vec::new();
+    for field in named {
+        let mut order = None;
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("summary")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("order") {
+                    order = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `order = ...` or `skip`"))
+                }
+            })?;
+        }
+        fields.push(Field {
+            name: field.ident.clone().expect("named fields have names"),
+            order,
+            skip,
+        });
+    }
+    Ok(fields)
+}
+
+// #[summary(template = "...")] on the struct, if there is one
+fn template(input: &DeriveInput) -> Result<Option<LitStr>> {
+    let mut template = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("summary")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("template") {
+                template = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `template = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(template)
+}
+
+// No template: the fields joined with commas
+fn in_order(fields: &[Field]) -> proc_macro2::TokenStream {
+    let mut shown: Vec<&Field> = fields.iter().filter(|f| !f.skip).collect();
+    // sort_by_key is stable, so fields without an order (or with the
+    // same one) stay in declaration order
+    shown.sort_by_key(|f| f.order.map_or((1, 0), |order| (0, order)));
+    let names = shown.iter().map(|f| &f.name);
+    quote! {
+        let parts: ::std::vec::Vec<::std::string::String> = ::std::vec![
+            #(::std::string::ToString::to_string(&self.#names)),*
+        ];
+        parts.join(", ")
+    }
+}
+
+// Hand the template to format! with every field it names passed by
+// name. format! does the real work (escaped braces, {field:>10} and so
+// on); all we have to do is check the names first, so a typo is an
+// error pointing at the template rather than at code the user can't see.
+fn with_template(
+    template: &LitStr,
+    fields: &[Field],
+    struct_name: &Ident,
+) -> Result<proc_macro2::TokenStream> {
+    let mut used: Vec<&Field> = Vec::new();
+    for name in placeholders(template)? {
+        let Some(field) = fields.iter().find(|f| f.name == name) else {
+            return Err(Error::new(
+                template.span(),
+                format!("no field `{}` on `{}`", name, struct_name),
+            ));
+        };
+        if field.skip {
+            return Err(Error::new(
+                template.span(),
+                format!("field `{}` is marked #[summary(skip)]", name),
+            ));
+        }
+        if !used.iter().any(|f| f.name == name) {
+            used.push(field);
+        }
+    }
+
+    let args = used.iter().map(|f| {
+        let name = &f.name;
+        quote! { #name = self.#name }
+    });
+    Ok(quote! {
+        ::std::format!(#template, #(#args),*)
+    })
+}
+
+// The field names inside the template's braces
+fn placeholders(template: &LitStr) -> Result<Vec<String>> {
+    let text = template.value();
+    let error = |message: &str| Error::new(template.span(), message);
+    let mut names = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '{' => {
+                let mut inside = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inside.push(c),
+                        None => return Err(error("unclosed `{` in template")),
+                    }
+                }
+                // Anything after a colon is formatting, not part of the name
+                let name = inside.split(':').next().unwrap_or("").trim();
+                if syn::parse_str::<Ident>(name).is_err() {
+                    return Err(error(
+                        "template placeholders must name a field, like {author}",
+                    ));
+                }
+                names.push(name.to_string());
+            }
+            '}' => return Err(error("unmatched `}` in template; write `}}` for a brace")),
+            _ => {}
+        }
+    }
+    Ok(names)
+}
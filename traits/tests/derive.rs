@@ -0,0 +1,8 @@
+// #[derive(Summary)] has to turn bad input into errors the user can
+// read. Each file in tests/ui is a program that shouldn't compile, next
+// to the error it should get (refresh those with TRYBUILD=overwrite).
+#[test]
+fn bad_derives_do_not_compile() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
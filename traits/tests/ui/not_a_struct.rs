@@ -0,0 +1,9 @@
+use cool_lib::Summary;
+
+#[derive(Summary)]
+enum Mood {
+    Happy,
+    Sad,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use cool_lib::Summary;
+
+#[derive(Summary)]
+#[summary(template = "{title} ({plays} plays)")]
+struct Song {
+    title: String,
+    #[summary(skip)]
+    plays: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use cool_lib::Summary;
+
+#[derive(Summary)]
+struct Song {
+    #[summary(hide)]
+    title: String,
+}
+
+fn main() {}
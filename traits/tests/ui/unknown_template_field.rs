@@ -0,0 +1,10 @@
+use cool_lib::Summary;
+
+#[derive(Summary)]
+#[summary(template = "{headline}, by {writer}")]
+struct Story {
+    headline: String,
+    author: String,
+}
+
+fn main() {}